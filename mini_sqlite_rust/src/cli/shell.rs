@@ -1,33 +1,87 @@
 /// Text-based interactive shell for the mini SQL engine.
 use crate::core::engine::DatabaseEngine;
+use crate::core::executor::QueryEvent;
+use crate::core::parser::{ParsedCommand, SQLParser, Value};
+use std::collections::HashMap;
 use std::io::{self, Write};
 
 const PROMPT: &str = "db> ";
+/// Shown instead of `PROMPT` while a statement begun on an earlier line is
+/// still waiting for its terminating `;`.
+const CONTINUATION_PROMPT: &str = "  -> ";
 const EXIT_COMMANDS: &[&str] = &["quit", "exit", ":q"];
 
 pub fn run_shell() {
     let mut engine = DatabaseEngine::new();
+    let parser = SQLParser::new();
+    // Named values set by `.param set` and consumed by `EXECUTE`'s
+    // `:name`/`$name` params; statements registered by `PREPARE name AS
+    // <sql>`, parsed once and replayed (with different bindings) by
+    // `EXECUTE name [USING ...]`.
+    let mut params: HashMap<String, Value> = HashMap::new();
+    let mut prepared: HashMap<String, ParsedCommand> = HashMap::new();
+    let mut accumulator = StatementAccumulator::new();
     println!("Welcome to the mini SQL shell. Type 'exit' to quit.");
 
     loop {
-        print!("{}", PROMPT);
+        print!(
+            "{}",
+            if accumulator.is_empty() {
+                PROMPT
+            } else {
+                CONTINUATION_PROMPT
+            }
+        );
         io::stdout().flush().unwrap();
 
-        let mut query = String::new();
-        match io::stdin().read_line(&mut query) {
-            Ok(0) => break, // EOF
-            Ok(_) => {
-                let trimmed = query.trim();
-                if trimmed.is_empty() {
-                    continue;
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) => {
+                if let Some(statement) = accumulator.take_partial() {
+                    run_statement(&statement, &mut engine);
                 }
-                if EXIT_COMMANDS.contains(&trimmed.to_lowercase().as_str()) {
-                    break;
+                break;
+            }
+            Ok(_) => {
+                if accumulator.is_empty() {
+                    let trimmed = line.trim();
+                    if trimmed.is_empty() {
+                        continue;
+                    }
+                    if EXIT_COMMANDS.contains(&trimmed.to_lowercase().as_str()) {
+                        break;
+                    }
+
+                    if let Some(rest) = trimmed.strip_prefix(".param ") {
+                        handle_param_command(rest, &parser, &mut params);
+                        continue;
+                    }
+                    if let Some(sql) = trimmed.strip_prefix(".subscribe ") {
+                        handle_subscribe(sql, &mut engine);
+                        continue;
+                    }
+                    if let Some(rest) = trimmed.strip_prefix(".unsubscribe ") {
+                        handle_unsubscribe(rest, &mut engine);
+                        continue;
+                    }
+                    if let Some(path) = trimmed.strip_prefix(".read ") {
+                        handle_read(path.trim(), &mut engine);
+                        continue;
+                    }
+
+                    let upper = trimmed.to_uppercase();
+                    if upper.starts_with("PREPARE ") {
+                        handle_prepare(trimmed, &parser, &mut prepared);
+                        continue;
+                    }
+                    if upper.starts_with("EXECUTE ") {
+                        handle_execute(trimmed, &parser, &prepared, &params, &mut engine);
+                        continue;
+                    }
                 }
 
-                let results = engine.execute(trimmed);
-                for line in results {
-                    println!("{}", line);
+                for statement in accumulator.feed(&line) {
+                    run_statement(&statement, &mut engine);
                 }
             }
             Err(error) => {
@@ -37,3 +91,240 @@ pub fn run_shell() {
         }
     }
 }
+
+/// Run one complete, already-split SQL statement and print its result lines.
+fn run_statement(statement: &str, engine: &mut DatabaseEngine) {
+    for line in engine.execute(statement) {
+        println!("{}", line);
+    }
+}
+
+/// Accumulates SQL text across calls until one or more complete statements
+/// appear, tracking quote state and parenthesis depth so a `;` inside a
+/// quoted string or a `VALUES(...)` tuple list doesn't end a statement
+/// early. Shared by the interactive prompt's line-at-a-time feeding and by
+/// `.read`, which feeds a whole script file in one call.
+struct StatementAccumulator {
+    buffer: String,
+    quote: Option<char>,
+    paren_depth: i32,
+}
+
+impl StatementAccumulator {
+    fn new() -> Self {
+        StatementAccumulator {
+            buffer: String::new(),
+            quote: None,
+            paren_depth: 0,
+        }
+    }
+
+    /// Whether no partial statement is buffered — used to pick the prompt.
+    fn is_empty(&self) -> bool {
+        self.buffer.trim().is_empty() && self.quote.is_none() && self.paren_depth == 0
+    }
+
+    /// Feed more text in, returning every complete statement it terminates
+    /// (trailing `;` stripped, in order). Text after the last `;` stays
+    /// buffered for the next call.
+    fn feed(&mut self, text: &str) -> Vec<String> {
+        let mut statements = Vec::new();
+        for ch in text.chars() {
+            match self.quote {
+                Some(q) => {
+                    self.buffer.push(ch);
+                    if ch == q {
+                        self.quote = None;
+                    }
+                }
+                None => match ch {
+                    '\'' | '"' => {
+                        self.quote = Some(ch);
+                        self.buffer.push(ch);
+                    }
+                    '(' => {
+                        self.paren_depth += 1;
+                        self.buffer.push(ch);
+                    }
+                    ')' => {
+                        self.paren_depth -= 1;
+                        self.buffer.push(ch);
+                    }
+                    ';' if self.paren_depth <= 0 => {
+                        let statement = self.buffer.trim().to_string();
+                        self.buffer.clear();
+                        if !statement.is_empty() {
+                            statements.push(statement);
+                        }
+                    }
+                    _ => self.buffer.push(ch),
+                },
+            }
+        }
+        statements
+    }
+
+    /// Drain whatever partial statement remains buffered, e.g. at EOF with
+    /// no trailing `;`.
+    fn take_partial(&mut self) -> Option<String> {
+        let statement = self.buffer.trim().to_string();
+        self.buffer.clear();
+        self.quote = None;
+        self.paren_depth = 0;
+        if statement.is_empty() {
+            None
+        } else {
+            Some(statement)
+        }
+    }
+}
+
+/// `.read <file>`: load `<file>` and run every statement in it in order,
+/// using the same statement splitter the interactive prompt does, so a
+/// multi-line `CREATE TABLE` or a batch of `INSERT`s can be loaded in one
+/// shot instead of pasted line by line.
+fn handle_read(path: &str, engine: &mut DatabaseEngine) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            println!("Error: failed to read '{}': {}", path, e);
+            return;
+        }
+    };
+    let mut accumulator = StatementAccumulator::new();
+    let mut statements = accumulator.feed(&content);
+    if let Some(trailing) = accumulator.take_partial() {
+        statements.push(trailing);
+    }
+    for statement in &statements {
+        run_statement(statement, engine);
+    }
+}
+
+/// `.param set <name> <value>`: stash a named value (the `:`/`$` sigil is
+/// optional) for later `EXECUTE`s to bind against `:name`/`$name` params.
+fn handle_param_command(rest: &str, parser: &SQLParser, params: &mut HashMap<String, Value>) {
+    let tokens: Vec<&str> = rest.splitn(3, ' ').collect();
+    if tokens.len() < 3 || tokens[0].to_lowercase() != "set" {
+        println!("Usage: .param set <name> <value>");
+        return;
+    }
+    let name = tokens[1]
+        .trim_start_matches(':')
+        .trim_start_matches('$')
+        .to_string();
+    params.insert(name, parser.parse_value(tokens[2].trim()));
+    println!("Parameter set.");
+}
+
+/// `PREPARE <name> AS <sql>`: parse `<sql>` once and remember it under
+/// `<name>` for later `EXECUTE`s, using the same `" AS "`/`" WHERE "`
+/// keyword-splitting idiom `parse_update`/`parse_delete` already use.
+fn handle_prepare(text: &str, parser: &SQLParser, prepared: &mut HashMap<String, ParsedCommand>) {
+    let upper = text.to_uppercase();
+    let as_idx = match upper.find(" AS ") {
+        Some(idx) => idx,
+        None => {
+            println!("Usage: PREPARE <name> AS <sql>");
+            return;
+        }
+    };
+    let header: Vec<&str> = text[..as_idx].split_whitespace().collect();
+    if header.len() < 2 {
+        println!("Usage: PREPARE <name> AS <sql>");
+        return;
+    }
+    let name = header[1].to_string();
+    let sql = text[as_idx + 4..].trim();
+    prepared.insert(name.clone(), parser.parse(sql));
+    println!("Statement '{}' prepared.", name);
+}
+
+/// `EXECUTE <name> [USING <v1>, <v2>, ...]`: bind `<name>`'s prepared
+/// statement against the positional values after `USING` (if any) and the
+/// `.param set` named values, then run the bound statement directly —
+/// skipping a re-parse, so bound values never get re-escaped into SQL text.
+fn handle_execute(
+    text: &str,
+    parser: &SQLParser,
+    prepared: &HashMap<String, ParsedCommand>,
+    params: &HashMap<String, Value>,
+    engine: &mut DatabaseEngine,
+) {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    if tokens.len() < 2 {
+        println!("Usage: EXECUTE <name> [USING <values>]");
+        return;
+    }
+    let name = tokens[1];
+    let statement = match prepared.get(name) {
+        Some(statement) => statement,
+        None => {
+            println!("Error: no prepared statement named '{}'.", name);
+            return;
+        }
+    };
+
+    let upper = text.to_uppercase();
+    let positional: Vec<Value> = match upper.find(" USING ") {
+        Some(idx) => text[idx + 7..]
+            .split(',')
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(|v| parser.parse_value(v))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let bound = statement.bind(&positional).bind_named(params);
+    for line in engine.execute_parsed(&bound) {
+        println!("{}", line);
+    }
+}
+
+/// `.subscribe <select>`: register `<select>` for live change notifications
+/// and spawn a background thread that prints each event as it arrives,
+/// prefixed with the subscription's id. The thread exits on its own once
+/// `.unsubscribe` drops the sending half of its channel.
+fn handle_subscribe(sql: &str, engine: &mut DatabaseEngine) {
+    match engine.subscribe(sql) {
+        Ok((id, receiver)) => {
+            println!("Subscribed as #{}.", id);
+            std::thread::spawn(move || {
+                for event in receiver {
+                    println!("[sub {}] {}", id, format_query_event(&event));
+                }
+            });
+        }
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+/// `.unsubscribe <id>`: cancel a subscription started by `.subscribe`.
+fn handle_unsubscribe(rest: &str, engine: &mut DatabaseEngine) {
+    match rest.trim().parse::<u64>() {
+        Ok(id) => {
+            if engine.unsubscribe(id) {
+                println!("Unsubscribed #{}.", id);
+            } else {
+                println!("Error: no subscription #{}.", id);
+            }
+        }
+        Err(_) => println!("Usage: .unsubscribe <id>"),
+    }
+}
+
+/// Render one `QueryEvent` the same `col: value` shape a formatted row uses.
+fn format_query_event(event: &QueryEvent) -> String {
+    let render_row = |row: &HashMap<String, Value>| {
+        let mut pairs: Vec<String> = row.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+        pairs.sort();
+        pairs.join(", ")
+    };
+    match event {
+        QueryEvent::Columns(headers) => format!("columns: {}", headers.join(", ")),
+        QueryEvent::Insert(row) => format!("insert ({})", render_row(row)),
+        QueryEvent::Update(row) => format!("update ({})", render_row(row)),
+        QueryEvent::Delete(row) => format!("delete ({})", render_row(row)),
+    }
+}
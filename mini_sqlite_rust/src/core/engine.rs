@@ -1,8 +1,9 @@
-use crate::core::executor::SQLExecutor;
+use crate::core::executor::{CursorId, QueryEvent, Row, SQLExecutor, StatementId, SubscriptionId};
 /// High level database engine wiring together parser, executor, and storage.
-use crate::core::parser::SQLParser;
+use crate::core::parser::{ParsedCommand, SQLParser, Value};
 use crate::core::storage::lsm_tree::LogEntry;
 use std::collections::HashMap;
+use std::sync::mpsc;
 
 pub struct DatabaseEngine {
     parser: SQLParser,
@@ -23,6 +24,50 @@ impl DatabaseEngine {
         self.executor.execute(&parsed)
     }
 
+    /// Column names a parsed `Select` command's result would carry; `None`
+    /// for commands that return no rows. Lets a front end announce a
+    /// result's shape (e.g. `RowDescription`) without re-running the query.
+    pub fn describe_result(&self, parsed: &ParsedCommand) -> Option<Vec<String>> {
+        self.executor.describe_result(parsed)
+    }
+
+    /// Parse and register `sql` for later `bind`/`execute_prepared` calls
+    /// without running it, recording the position of every `?`/`$N`
+    /// placeholder so binding can substitute values without re-parsing.
+    pub fn prepare(&mut self, sql: &str) -> StatementId {
+        let parsed = self.parser.parse(sql);
+        self.executor.prepare(parsed)
+    }
+
+    /// Column names a prepared statement's result would carry; `None` for
+    /// statements that return no rows or an unknown `id`.
+    pub fn describe_prepared(&self, id: StatementId) -> Option<Vec<String>> {
+        self.executor.describe_prepared(id)
+    }
+
+    /// The number of placeholders a prepared statement expects.
+    pub fn prepared_param_count(&self, id: StatementId) -> Option<usize> {
+        self.executor.prepared_param_count(id)
+    }
+
+    /// Bind `params` to a prepared statement, failing if the count doesn't
+    /// match the number of placeholders it was parsed with.
+    pub fn bind(&mut self, id: StatementId, params: Vec<Value>) -> Result<(), String> {
+        self.executor.bind(id, params)
+    }
+
+    /// Substitute the statement's most recently bound parameters and run it.
+    pub fn execute_prepared(&mut self, id: StatementId) -> Result<Vec<String>, String> {
+        self.executor.execute_prepared(id)
+    }
+
+    /// Run an already-parsed statement directly, skipping a second parse.
+    /// Used by the shell's `PREPARE`/`EXECUTE` flow so a `ParsedCommand::bind`
+    /// result never has to be re-escaped back into SQL text.
+    pub fn execute_parsed(&mut self, parsed: &ParsedCommand) -> Vec<String> {
+        self.executor.execute(parsed)
+    }
+
     /// Return a snapshot of databases, tables, and columns.
     pub fn describe(&self) -> HashMap<String, HashMap<String, serde_json::Value>> {
         self.executor.describe()
@@ -33,6 +78,78 @@ impl DatabaseEngine {
         self.executor.active_database()
     }
 
+    /// Restore a caller's session-scoped active database before running its
+    /// query, so one connection's `USE` can't leak into another's.
+    pub fn set_active_database(&mut self, name: &str) {
+        self.executor.set_active_database(name);
+    }
+
+    /// How many mutations are buffered against the open transaction, if any.
+    pub fn transaction_status(&self) -> Option<usize> {
+        self.executor.transaction_status()
+    }
+
+    /// Cancel a transaction a request left open without committing it.
+    pub fn discard_open_transaction(&mut self) -> bool {
+        self.executor.discard_open_transaction()
+    }
+
+    /// Parse `sql` (which must be a `SELECT`) and register its rows as a
+    /// cursor, so a caller can page through them with `fetch_cursor` instead
+    /// of formatting the whole result at once.
+    pub fn open_cursor(&mut self, sql: &str) -> Result<CursorId, String> {
+        let parsed = self.parser.parse(sql);
+        self.executor.open_cursor(&parsed)
+    }
+
+    /// Pull the next `n` rows from `id`, and whether any rows remain after them.
+    pub fn fetch_cursor(&mut self, id: CursorId, n: usize) -> Option<(Vec<String>, bool)> {
+        self.executor.fetch_cursor(id, n)
+    }
+
+    /// Column headers `id`'s rows carry.
+    pub fn cursor_headers(&self, id: CursorId) -> Option<Vec<String>> {
+        self.executor.cursor_headers(id)
+    }
+
+    /// Total rows `id`'s `SELECT` matched, independent of how many have
+    /// been fetched so far.
+    pub fn cursor_total_rows(&self, id: CursorId) -> Option<usize> {
+        self.executor.cursor_total_rows(id)
+    }
+
+    /// Release a cursor's buffered rows.
+    pub fn close_cursor(&mut self, id: CursorId) -> bool {
+        self.executor.close_cursor(id)
+    }
+
+    /// Parse and run `sql` (which must be a `SELECT`), returning its column
+    /// headers and rows typed rather than formatted to text, for callers
+    /// like the JSON query endpoint that want structured rows instead of the
+    /// `execute`/`Vec<String>` text path.
+    pub fn query_rows(&self, sql: &str) -> Result<(Vec<String>, Vec<Row>), String> {
+        let parsed = self.parser.parse(sql);
+        self.executor.query_rows(&parsed)
+    }
+
+    /// Register `sql` (a `SELECT`) for live change notifications, returning
+    /// its id and the receiving end of the channel events arrive on.
+    pub fn subscribe(&mut self, sql: &str) -> Result<(SubscriptionId, mpsc::Receiver<QueryEvent>), String> {
+        self.executor.subscribe(sql)
+    }
+
+    /// Cancel a subscription. Returns whether one existed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.executor.unsubscribe(id)
+    }
+
+    /// The most recent `n` entries of the `__query_log` audit table,
+    /// formatted the same way a `SELECT`'s rows are, for the HTML
+    /// "Recent activity" panel.
+    pub fn audit_log_tail(&self, n: usize) -> Vec<String> {
+        self.executor.audit_log_tail(n)
+    }
+
     pub fn databases(&self) -> Vec<String> {
         self.executor.databases()
     }
@@ -1,17 +1,242 @@
 /// SQL executor orchestrating DDL, DML, and simple commits.
-use crate::core::parser::{CommandType, ParsedCommand, Value};
+use crate::core::parser::{
+    AggregateFunc, CommandType, Expr, JoinInfo, OrderKey, ParsedCommand, ResultColumn, SQLParser, Value,
+};
 use crate::core::storage::btree::BTreeStorage;
 use crate::core::storage::lsm_tree::{LSMTreeStorage, LogEntry};
+use crate::core::storage::memory::MemoryStorage;
 use crate::core::storage::pager::Pager;
-use std::collections::HashMap;
+use crate::core::storage::storage_engine::StorageEngine;
+use crate::core::storage::table_store::{values_compare, TableStore};
+use crate::core::storage::wal::Wal;
+use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the internal system table `SELECT`s can read the audit log
+/// through, e.g. `SELECT * FROM __query_log`.
+const AUDIT_LOG_TABLE: &str = "__query_log";
+
+/// Oldest entries are evicted once the audit log holds this many, so a
+/// runaway client can't grow it unbounded over a long-running process.
+const AUDIT_LOG_MAX_ENTRIES: usize = 500;
+
+/// Longest SQL text or error message the audit log stores verbatim; longer
+/// text is truncated with a trailing ellipsis.
+const AUDIT_LOG_TEXT_LIMIT: usize = 1000;
+
+/// A single buffered mutation awaiting `COMMIT`, stamped with the sequence
+/// number it will take effect at.
+enum PendingMutation {
+    Insert {
+        table: String,
+        values: Vec<Value>,
+    },
+    Update {
+        table: String,
+        assignments: HashMap<String, Value>,
+        condition: Option<Expr>,
+    },
+    Delete {
+        table: String,
+        condition: Option<Expr>,
+    },
+}
+
+/// An open `BEGIN ... COMMIT/ROLLBACK` block: a read snapshot plus the
+/// mutations buffered against it, modeled on LevelDB's sequence-number
+/// snapshots. Mutations only become visible outside the transaction once
+/// `COMMIT` applies them to the storage engine.
+struct Transaction {
+    snapshot_seq: u64,
+    /// Buffered mutations paired with the sequence number assigned when they
+    /// were queued.
+    pending: Vec<(u64, PendingMutation)>,
+}
+
+/// Identifies a statement parsed once by `prepare` and replayed by
+/// `execute_prepared` with different bound parameters.
+pub type StatementId = u64;
+
+/// Identifies a `SELECT` opened by `open_cursor` for paginated fetching.
+pub type CursorId = u64;
+
+/// A `SELECT`'s full row set, materialized once by `open_cursor` and handed
+/// out a page at a time by `fetch_cursor`. Storage engines here have no
+/// notion of lazy iteration, so this only avoids re-running the query (and
+/// re-formatting every row) on each page; it does not reduce peak memory use
+/// the way a true server-side cursor would.
+struct Cursor {
+    headers: Vec<String>,
+    rows: Vec<Row>,
+    offset: usize,
+}
+
+/// A row-level event pushed to a subscriber after a mutation changes which
+/// rows match its `SELECT`. `Columns` is sent once at subscribe time so a
+/// receiver knows the result shape before the first row event arrives.
+#[derive(Debug, Clone)]
+pub enum QueryEvent {
+    Columns(Vec<String>),
+    Insert(HashMap<String, Value>),
+    Update(HashMap<String, Value>),
+    Delete(HashMap<String, Value>),
+}
+
+/// Identifies a live `SELECT` registered by `subscribe`, until `unsubscribe`
+/// cancels it.
+pub type SubscriptionId = u64;
+
+/// A registered live `SELECT`: the table and predicate to test mutations
+/// against, the rows it matched as of the last notification (diffed against
+/// after the next one), and the channel events are pushed down. Rows here
+/// have no identity that survives an `UPDATE`, so a mutation that changes a
+/// matched row's values without changing how many rows match is reported as
+/// a single `Update`; a mutation that changes the match count is reported as
+/// plain `Insert`/`Delete` events instead.
+struct Subscription {
+    table: String,
+    condition: Option<Expr>,
+    last_snapshot: Vec<HashMap<String, Value>>,
+    sender: mpsc::Sender<QueryEvent>,
+}
+
+/// One row of the durable `__query_log` audit table: what ran, against which
+/// database, whether it succeeded, and how many rows it touched. SQL text is
+/// taken from `ParsedCommand::raw`, which for a substituted prepared
+/// statement still holds the original `?`/`$N` placeholders rather than the
+/// bound values, so logged text never exposes parameters passed by bind.
+struct AuditEntry {
+    timestamp_millis: u64,
+    database: String,
+    sql: String,
+    success: bool,
+    error: Option<String>,
+    rows_affected: Option<usize>,
+}
+
+/// A single `SELECT` result row, as an ordered list of typed values aligned
+/// with `columns` — the shared representation `format_rows`, `fetch_cursor`,
+/// and the JSON query endpoint all format over, instead of the engine
+/// flattening everything to `Vec<String>` up front.
+pub struct Row {
+    pub columns: Vec<String>,
+    pub values: Vec<Value>,
+}
+
+impl Row {
+    /// The value in this row's `index`-th column, if any.
+    pub fn get(&self, index: usize) -> Option<&Value> {
+        self.values.get(index)
+    }
+}
+
+/// Decode a single column value into a concrete Rust type, so `FromRow` can
+/// pull typed values out of a `Row` by position.
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, String>;
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        Ok(value.clone())
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Integer(n) => Ok(*n),
+            other => Err(format!("expected an integer, got {}", other)),
+        }
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Float(f) => Ok(*f),
+            Value::Integer(n) => Ok(*n as f64),
+            other => Err(format!("expected a float, got {}", other)),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Text(s) => Ok(s.clone()),
+            other => Err(format!("expected text, got {}", other)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+/// Decode a whole `Row` by position, e.g. `<(i64, String)>::from_row(&row)`,
+/// rather than reaching into its columns by name.
+pub trait FromRow: Sized {
+    fn from_row(row: &Row) -> Result<Self, String>;
+}
+
+impl<A: FromValue> FromRow for (A,) {
+    fn from_row(row: &Row) -> Result<Self, String> {
+        let a = row.get(0).ok_or_else(|| "row has no column 0".to_string())?;
+        Ok((A::from_value(a)?,))
+    }
+}
+
+impl<A: FromValue, B: FromValue> FromRow for (A, B) {
+    fn from_row(row: &Row) -> Result<Self, String> {
+        let a = row.get(0).ok_or_else(|| "row has no column 0".to_string())?;
+        let b = row.get(1).ok_or_else(|| "row has no column 1".to_string())?;
+        Ok((A::from_value(a)?, B::from_value(b)?))
+    }
+}
+
+impl<A: FromValue, B: FromValue, C: FromValue> FromRow for (A, B, C) {
+    fn from_row(row: &Row) -> Result<Self, String> {
+        let a = row.get(0).ok_or_else(|| "row has no column 0".to_string())?;
+        let b = row.get(1).ok_or_else(|| "row has no column 1".to_string())?;
+        let c = row.get(2).ok_or_else(|| "row has no column 2".to_string())?;
+        Ok((A::from_value(a)?, B::from_value(b)?, C::from_value(c)?))
+    }
+}
 
 pub struct SQLExecutor {
     lsm: LSMTreeStorage,
-    databases: HashMap<String, BTreeStorage>,
+    databases: HashMap<String, Box<dyn StorageEngine>>,
+    wals: HashMap<String, Wal>,
     active_db: String,
     data_dir: PathBuf,
+    /// Monotonically increasing sequence counter; every buffered mutation is
+    /// stamped with the value it is assigned at.
+    next_seq: u64,
+    txn: Option<Transaction>,
+    /// Statements registered by `prepare`, keyed by `StatementId`, still
+    /// holding their `Value::Placeholder` slots until `bind` substitutes them.
+    prepared: HashMap<StatementId, ParsedCommand>,
+    /// The most recent parameters bound to each prepared statement.
+    bound_params: HashMap<StatementId, Vec<Value>>,
+    next_statement_id: StatementId,
+    cursors: HashMap<CursorId, Cursor>,
+    next_cursor_id: CursorId,
+    /// Live `SELECT`s registered by `subscribe`, notified after a mutation
+    /// touches their table.
+    subscriptions: HashMap<SubscriptionId, Subscription>,
+    next_subscription_id: SubscriptionId,
+    /// Durable audit log of every statement run, oldest first; queryable as
+    /// `__query_log` and rendered as a tail in the HTML log panel.
+    audit_log: VecDeque<AuditEntry>,
 }
 
 impl SQLExecutor {
@@ -19,10 +244,21 @@ impl SQLExecutor {
         let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
         let _ = fs::create_dir_all(&data_dir);
         let mut executor = SQLExecutor {
-            lsm: LSMTreeStorage::new(),
+            lsm: LSMTreeStorage::open(data_dir.join("lsm")),
             databases: HashMap::new(),
+            wals: HashMap::new(),
             active_db: "default".to_string(),
             data_dir,
+            next_seq: 0,
+            txn: None,
+            prepared: HashMap::new(),
+            bound_params: HashMap::new(),
+            next_statement_id: 0,
+            cursors: HashMap::new(),
+            next_cursor_id: 0,
+            subscriptions: HashMap::new(),
+            next_subscription_id: 0,
+            audit_log: VecDeque::new(),
         };
         executor.load_databases();
         if executor.databases.is_empty() {
@@ -36,14 +272,23 @@ impl SQLExecutor {
         executor
     }
 
+    /// Run `parsed`, recording an audit-log entry for it (skipping blank
+    /// input and reads of the audit log itself, to avoid churning the log
+    /// with noise it produced).
     pub fn execute(&mut self, parsed: &ParsedCommand) -> Vec<String> {
+        let result = self.execute_command(parsed);
+        self.record_audit(parsed, &result);
+        result
+    }
+
+    fn execute_command(&mut self, parsed: &ParsedCommand) -> Vec<String> {
         match &parsed.command {
             CommandType::Empty => vec![String::new()],
 
-            CommandType::CreateDatabase { name } => {
-                self.ensure_database(name);
+            CommandType::CreateDatabase { name, engine } => {
+                self.ensure_database_with_engine(name, engine);
                 self.active_db = name.clone();
-                vec![format!("Database '{}' ready.", name)]
+                vec![format!("Database '{}' ready ({} engine).", name, engine)]
             }
 
             CommandType::AlterDatabase { name } => {
@@ -107,21 +352,55 @@ impl SQLExecutor {
                 vec![format!("Index on {}.{} removed.", table, column)]
             }
 
-            CommandType::Insert { table, values } => {
-                let storage = self.databases.get_mut(&self.active_db).unwrap();
+            CommandType::Insert { table, columns, rows } => {
+                let storage = match self.databases.get(&self.active_db) {
+                    Some(storage) => storage,
+                    None => return self.txn_error(format!("Database '{}' not found.", self.active_db)),
+                };
                 if !storage.table_exists(table) {
-                    return vec![format!("Table '{}' not found.", table)];
+                    return self.txn_error(format!("Table '{}' not found.", table));
                 }
-                match storage.insert_row(table, values.clone()) {
-                    Ok(_row) => {
-                        let mut details = HashMap::new();
-                        details.insert("table".to_string(), serde_json::to_value(table).unwrap());
-                        self.lsm.log(LogEntry {
-                            db: self.active_db.clone(),
-                            command: "INSERT".to_string(),
-                            details,
+                let table_columns = storage.columns(table).unwrap_or_default();
+                let mapped: Result<Vec<Vec<Value>>, String> = rows
+                    .iter()
+                    .map(|row| Self::map_insert_row(&table_columns, columns.as_deref(), row))
+                    .collect();
+                let mapped = match mapped {
+                    Ok(mapped) => mapped,
+                    Err(e) => return self.txn_error(format!("Error: {}", e)),
+                };
+
+                if self.txn.is_some() {
+                    let count = mapped.len();
+                    for values in mapped {
+                        self.queue_mutation(PendingMutation::Insert {
+                            table: table.clone(),
+                            values,
                         });
-                        vec!["1 row inserted.".to_string()]
+                    }
+                    let word = if count == 1 { "row" } else { "rows" };
+                    return vec![format!("{} {} queued (pending commit).", count, word)];
+                }
+
+                let mut details = HashMap::new();
+                details.insert("table".to_string(), serde_json::to_value(table).unwrap());
+                details.insert("values".to_string(), serde_json::to_value(&mapped).unwrap());
+                let entry = LogEntry {
+                    db: self.active_db.clone(),
+                    command: "INSERT".to_string(),
+                    details,
+                };
+                if let Err(e) = self.append_wal(&entry) {
+                    return vec![format!("Error: failed to write WAL: {}", e)];
+                }
+
+                let storage = self.databases.get_mut(&self.active_db).unwrap();
+                match storage.insert_rows(table, mapped) {
+                    Ok(inserted) => {
+                        self.lsm.log(entry);
+                        self.notify_subscriptions(table);
+                        let word = if inserted.len() == 1 { "row" } else { "rows" };
+                        vec![format!("{} {} inserted.", inserted.len(), word)]
                     }
                     Err(e) => vec![format!("Error: {}", e)],
                 }
@@ -132,19 +411,41 @@ impl SQLExecutor {
                 assignments,
                 condition,
             } => {
-                let storage = self.databases.get_mut(&self.active_db).unwrap();
-                if !storage.table_exists(table) {
-                    return vec![format!("Table '{}' not found.", table)];
+                if !self.databases.get(&self.active_db).unwrap().table_exists(table) {
+                    return self.txn_error(format!("Table '{}' not found.", table));
+                }
+                if self.txn.is_some() {
+                    self.queue_mutation(PendingMutation::Update {
+                        table: table.clone(),
+                        assignments: assignments.clone(),
+                        condition: condition.clone(),
+                    });
+                    return vec!["0 row(s) queued (pending commit).".to_string()];
                 }
+                let mut details = HashMap::new();
+                details.insert("table".to_string(), serde_json::to_value(table).unwrap());
+                details.insert(
+                    "assignments".to_string(),
+                    serde_json::to_value(assignments).unwrap(),
+                );
+                details.insert(
+                    "condition".to_string(),
+                    serde_json::to_value(condition).unwrap(),
+                );
+                let entry = LogEntry {
+                    db: self.active_db.clone(),
+                    command: "UPDATE".to_string(),
+                    details,
+                };
+                if let Err(e) = self.append_wal(&entry) {
+                    return vec![format!("Error: failed to write WAL: {}", e)];
+                }
+
+                let storage = self.databases.get_mut(&self.active_db).unwrap();
                 match storage.update_rows(table, assignments, condition.as_ref()) {
                     Ok(count) => {
-                        let mut details = HashMap::new();
-                        details.insert("count".to_string(), serde_json::to_value(count).unwrap());
-                        self.lsm.log(LogEntry {
-                            db: self.active_db.clone(),
-                            command: "UPDATE".to_string(),
-                            details,
-                        });
+                        self.lsm.log(entry);
+                        self.notify_subscriptions(table);
                         vec![format!("{} row(s) updated.", count)]
                     }
                     Err(e) => vec![format!("Error: {}", e)],
@@ -152,19 +453,36 @@ impl SQLExecutor {
             }
 
             CommandType::Delete { table, condition } => {
-                let storage = self.databases.get_mut(&self.active_db).unwrap();
-                if !storage.table_exists(table) {
-                    return vec![format!("Table '{}' not found.", table)];
+                if !self.databases.get(&self.active_db).unwrap().table_exists(table) {
+                    return self.txn_error(format!("Table '{}' not found.", table));
+                }
+                if self.txn.is_some() {
+                    self.queue_mutation(PendingMutation::Delete {
+                        table: table.clone(),
+                        condition: condition.clone(),
+                    });
+                    return vec!["0 row(s) queued (pending commit).".to_string()];
                 }
+                let mut details = HashMap::new();
+                details.insert("table".to_string(), serde_json::to_value(table).unwrap());
+                details.insert(
+                    "condition".to_string(),
+                    serde_json::to_value(condition).unwrap(),
+                );
+                let entry = LogEntry {
+                    db: self.active_db.clone(),
+                    command: "DELETE".to_string(),
+                    details,
+                };
+                if let Err(e) = self.append_wal(&entry) {
+                    return vec![format!("Error: failed to write WAL: {}", e)];
+                }
+
+                let storage = self.databases.get_mut(&self.active_db).unwrap();
                 match storage.delete_rows(table, condition.as_ref()) {
                     Ok(count) => {
-                        let mut details = HashMap::new();
-                        details.insert("count".to_string(), serde_json::to_value(count).unwrap());
-                        self.lsm.log(LogEntry {
-                            db: self.active_db.clone(),
-                            command: "DELETE".to_string(),
-                            details,
-                        });
+                        self.lsm.log(entry);
+                        self.notify_subscriptions(table);
                         vec![format!("{} row(s) deleted.", count)]
                     }
                     Err(e) => vec![format!("Error: {}", e)],
@@ -175,27 +493,71 @@ impl SQLExecutor {
                 table,
                 columns,
                 condition,
-                join,
-            } => {
-                let storage = self.databases.get(&self.active_db).unwrap();
-                if !storage.table_exists(table) {
-                    return vec![format!("Table '{}' not found.", table)];
+                joins,
+                order_by,
+                limit,
+                offset,
+                group_by,
+            } => match self.run_select(table, condition.as_ref(), joins) {
+                Ok(rows) => {
+                    let (headers, shaped) =
+                        Self::shape_select(rows, columns, group_by, order_by, *limit, *offset);
+                    let typed = Self::rows_to_typed(&headers, shaped);
+                    self.format_rows(&typed)
                 }
-                if let Some(join_info) = join {
-                    if !storage.table_exists(&join_info.table) {
-                        return vec![format!("Table '{}' not found.", join_info.table)];
-                    }
+                Err(e) => vec![format!("Error: {}", e)],
+            },
+
+            CommandType::Begin => {
+                if let Some(txn) = &self.txn {
+                    return vec![format!(
+                        "A transaction is already in progress (snapshot seq {}).",
+                        txn.snapshot_seq
+                    )];
                 }
-                match storage.select_rows(table, columns, condition.as_ref(), join.as_ref()) {
-                    Ok(rows) => self.format_rows(&rows, columns),
-                    Err(e) => vec![format!("Error: {}", e)],
+                let snapshot_seq = self.next_seq;
+                self.txn = Some(Transaction {
+                    snapshot_seq,
+                    pending: Vec::new(),
+                });
+                if let Some(storage) = self.databases.get_mut(&self.active_db) {
+                    storage.begin_txn();
                 }
+                vec![format!("Transaction started at snapshot seq {}.", snapshot_seq)]
             }
 
+            CommandType::Rollback => match self.discard_transaction() {
+                Some(count) => vec![format!(
+                    "Transaction rolled back, discarding {} pending mutation(s).",
+                    count
+                )],
+                None => vec!["No transaction in progress.".to_string()],
+            },
+
             CommandType::Commit => {
+                if let Some(txn) = self.txn.take() {
+                    return match self.apply_transaction(txn) {
+                        Ok(applied) => {
+                            if let Some(wal) = self.wals.get_mut(&self.active_db) {
+                                let _ = wal.checkpoint();
+                            }
+                            vec![format!(
+                                "Committed transaction: {} mutation(s) applied.",
+                                applied
+                            )]
+                        }
+                        Err(e) => vec![format!(
+                            "Error: {}; transaction rolled back, no mutations applied.",
+                            e
+                        )],
+                    };
+                }
                 let entries = self.lsm.commit();
                 let count = entries.len();
                 let entry_word = if count == 1 { "entry" } else { "entries" };
+                if let Some(wal) = self.wals.get_mut(&self.active_db) {
+                    let _ = wal.checkpoint();
+                }
                 vec![format!("Committed {} {}.", count, entry_word)]
             }
 
@@ -205,46 +567,1258 @@ impl SQLExecutor {
         }
     }
 
+    /// Queue a mutation on the open transaction, stamping it with the next
+    /// sequence number.
+    fn queue_mutation(&mut self, mutation: PendingMutation) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        if let Some(txn) = &mut self.txn {
+            txn.pending.push((seq, mutation));
+        }
+    }
+
+    /// Apply a committed transaction's buffered mutations to the active
+    /// database, in the order they were queued; each keeps the sequence
+    /// number it was stamped with when queued. Atomic: the first mutation that fails aborts the
+    /// whole batch and rolls back everything applied so far, same as if the
+    /// client had sent `ROLLBACK` instead of `COMMIT`.
+    fn apply_transaction(&mut self, txn: Transaction) -> Result<usize, String> {
+        if !self.databases.contains_key(&self.active_db) {
+            return Ok(0);
+        }
+        let mut applied = 0;
+        let mut touched_tables: Vec<String> = Vec::new();
+        let mut logged: Vec<LogEntry> = Vec::new();
+        for (_seq, mutation) in txn.pending {
+            let table_name = match &mutation {
+                PendingMutation::Insert { table, .. }
+                | PendingMutation::Update { table, .. }
+                | PendingMutation::Delete { table, .. } => table.clone(),
+            };
+            let entry = Self::mutation_log_entry(&self.active_db, &mutation);
+            // Write the WAL record before applying the mutation, same
+            // write-WAL-then-storage ordering the non-transactional
+            // INSERT/UPDATE/DELETE branches use, so a crash mid-`persist()`
+            // has a WAL record to replay for a transactional write too,
+            // not just a single-statement one.
+            if let Err(e) = self.append_wal(&entry) {
+                let storage = self.databases.get_mut(&self.active_db).unwrap();
+                storage.rollback_txn();
+                return Err(format!("failed to write WAL: {}", e));
+            }
+
+            let storage = self.databases.get_mut(&self.active_db).unwrap();
+            let result = match mutation {
+                PendingMutation::Insert { table, values } => {
+                    storage.insert_row(&table, values).map(|_| ())
+                }
+                PendingMutation::Update {
+                    table,
+                    assignments,
+                    condition,
+                } => storage
+                    .update_rows(&table, &assignments, condition.as_ref())
+                    .map(|_| ()),
+                PendingMutation::Delete { table, condition } => storage
+                    .delete_rows(&table, condition.as_ref())
+                    .map(|_| ()),
+            };
+            match result {
+                Ok(_) => {
+                    applied += 1;
+                    logged.push(entry);
+                    if !touched_tables.contains(&table_name) {
+                        touched_tables.push(table_name);
+                    }
+                }
+                Err(e) => {
+                    let storage = self.databases.get_mut(&self.active_db).unwrap();
+                    storage.rollback_txn();
+                    return Err(e);
+                }
+            }
+        }
+        let storage = self.databases.get_mut(&self.active_db).unwrap();
+        storage.commit_txn();
+        // Buffered mutations only reach `lsm` once the transaction commits,
+        // in the same order they were applied above, so the "Pending log"
+        // panel shows them as committed entries rather than understating
+        // history for every transactional write.
+        for entry in logged {
+            self.lsm.log(entry);
+        }
+        for table in &touched_tables {
+            self.notify_subscriptions(table);
+        }
+        Ok(applied)
+    }
+
+    /// Build the same `LogEntry` shape the non-transactional INSERT/UPDATE/
+    /// DELETE branches log immediately, so a mutation applied via
+    /// `apply_transaction` is indistinguishable in `lsm_entries` from one
+    /// applied outside a transaction.
+    fn mutation_log_entry(db: &str, mutation: &PendingMutation) -> LogEntry {
+        let (command, details) = match mutation {
+            PendingMutation::Insert { table, values } => {
+                let mut details = HashMap::new();
+                details.insert("table".to_string(), serde_json::to_value(table).unwrap());
+                details.insert("values".to_string(), serde_json::to_value(&vec![values.clone()]).unwrap());
+                ("INSERT".to_string(), details)
+            }
+            PendingMutation::Update {
+                table,
+                assignments,
+                condition,
+            } => {
+                let mut details = HashMap::new();
+                details.insert("table".to_string(), serde_json::to_value(table).unwrap());
+                details.insert(
+                    "assignments".to_string(),
+                    serde_json::to_value(assignments).unwrap(),
+                );
+                details.insert(
+                    "condition".to_string(),
+                    serde_json::to_value(condition).unwrap(),
+                );
+                ("UPDATE".to_string(), details)
+            }
+            PendingMutation::Delete { table, condition } => {
+                let mut details = HashMap::new();
+                details.insert("table".to_string(), serde_json::to_value(table).unwrap());
+                details.insert(
+                    "condition".to_string(),
+                    serde_json::to_value(condition).unwrap(),
+                );
+                ("DELETE".to_string(), details)
+            }
+        };
+        LogEntry {
+            db: db.to_string(),
+            command,
+            details,
+        }
+    }
+
+    /// Discard the open transaction (if any), undoing any mutations already
+    /// applied against its storage snapshot. Returns the number of buffered
+    /// mutations that were dropped, or `None` if no transaction was open.
+    /// Shared by the explicit `ROLLBACK` statement and by callers that need
+    /// to cancel a transaction a request left open without committing it
+    /// (e.g. the HTTP server, where a transaction can't outlive its request).
+    fn discard_transaction(&mut self) -> Option<usize> {
+        let txn = self.txn.take()?;
+        if let Some(storage) = self.databases.get_mut(&self.active_db) {
+            storage.rollback_txn();
+        }
+        Some(txn.pending.len())
+    }
+
+    /// Return `message` as the command's result, discarding the open
+    /// transaction (if any) first. A statement that fails before it's even
+    /// queued — e.g. an `INSERT` naming a table that doesn't exist — still
+    /// means the transaction can't be trusted to commit only what the
+    /// client intended, so it must be dropped the same as an explicit
+    /// `ROLLBACK` would, not left open for a later `COMMIT` to silently
+    /// apply just the statements that happened to already succeed.
+    fn txn_error(&mut self, message: String) -> Vec<String> {
+        if self.txn.is_some() {
+            self.discard_transaction();
+        }
+        vec![message]
+    }
+
+    /// Whether a transaction is currently open, and how many mutations are
+    /// buffered against it — used to render the pending-vs-committed split
+    /// in the HTML "Pending log" panel.
+    pub fn transaction_status(&self) -> Option<usize> {
+        self.txn.as_ref().map(|txn| txn.pending.len())
+    }
+
+    /// Cancel any transaction left open at the end of a request without an
+    /// explicit `COMMIT`/`ROLLBACK`, so it can't linger past its request and
+    /// block every other connection from starting one of its own.
+    pub fn discard_open_transaction(&mut self) -> bool {
+        self.discard_transaction().is_some()
+    }
+
+    /// Resolve a `SELECT`'s rows, taking the open transaction's snapshot into
+    /// account the same way the `Select` command does. Shared by `execute`
+    /// and `open_cursor` so a cursor sees exactly what a plain `SELECT`
+    /// would have returned.
+    fn run_select(
+        &self,
+        table: &str,
+        condition: Option<&Expr>,
+        joins: &[JoinInfo],
+    ) -> Result<Vec<HashMap<String, Value>>, String> {
+        if table == AUDIT_LOG_TABLE {
+            if !joins.is_empty() {
+                return Err(format!("'{}' does not support joins.", AUDIT_LOG_TABLE));
+            }
+            return Ok(self.audit_log_rows(condition));
+        }
+        let storage = self
+            .databases
+            .get(&self.active_db)
+            .ok_or_else(|| format!("Database '{}' not found.", self.active_db))?;
+        if !storage.table_exists(table) {
+            return Err(format!("Table '{}' not found.", table));
+        }
+        for join_info in joins {
+            if !storage.table_exists(&join_info.table) {
+                return Err(format!("Table '{}' not found.", join_info.table));
+            }
+        }
+        if self.txn.is_some() && joins.is_empty() {
+            return self.select_with_snapshot(table, condition);
+        }
+        storage.select_rows(table, &["*".to_string()], condition, joins)
+    }
+
+    /// Run a `SELECT` and register its full row set as a cursor a caller can
+    /// page through with `fetch_cursor` instead of formatting everything at
+    /// once. Fails the same way `execute` would for a non-`SELECT` command
+    /// or a missing table.
+    pub fn open_cursor(&mut self, parsed: &ParsedCommand) -> Result<CursorId, String> {
+        let (table, columns, condition, joins, order_by, limit, offset, group_by) = match &parsed.command {
+            CommandType::Select {
+                table,
+                columns,
+                condition,
+                joins,
+                order_by,
+                limit,
+                offset,
+                group_by,
+            } => (
+                table,
+                columns,
+                condition.as_ref(),
+                joins,
+                order_by,
+                *limit,
+                *offset,
+                group_by,
+            ),
+            _ => return Err("Only SELECT statements can be opened as a cursor.".to_string()),
+        };
+
+        let rows = self.run_select(table, condition, joins)?;
+        let (headers, shaped) = Self::shape_select(rows, columns, group_by, order_by, limit, offset);
+        let typed_rows = Self::rows_to_typed(&headers, shaped);
+
+        let id = self.next_cursor_id;
+        self.next_cursor_id += 1;
+        self.cursors.insert(
+            id,
+            Cursor {
+                headers,
+                rows: typed_rows,
+                offset: 0,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Pull the next `n` rows from a cursor, formatted the same way a plain
+    /// `SELECT`'s rows are, advancing past them. Returns the formatted rows
+    /// and whether any rows remain after this page; `None` for an unknown
+    /// (or already-closed) cursor.
+    pub fn fetch_cursor(&mut self, id: CursorId, n: usize) -> Option<(Vec<String>, bool)> {
+        let cursor = self.cursors.get_mut(&id)?;
+        let end = (cursor.offset + n).min(cursor.rows.len());
+        let lines: Vec<String> = cursor.rows[cursor.offset..end]
+            .iter()
+            .map(|row| {
+                row.values
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            })
+            .collect();
+        cursor.offset = end;
+        let has_more = cursor.offset < cursor.rows.len();
+        Some((lines, has_more))
+    }
+
+    /// Column headers a cursor's rows carry, e.g. to render a table header
+    /// alongside the pages `fetch_cursor` returns.
+    pub fn cursor_headers(&self, id: CursorId) -> Option<Vec<String>> {
+        self.cursors.get(&id).map(|cursor| cursor.headers.clone())
+    }
+
+    /// Total row count a cursor's `SELECT` matched, regardless of how much
+    /// of it has been fetched so far.
+    pub fn cursor_total_rows(&self, id: CursorId) -> Option<usize> {
+        self.cursors.get(&id).map(|cursor| cursor.rows.len())
+    }
+
+    /// Release a cursor's buffered rows. Returns whether one existed.
+    pub fn close_cursor(&mut self, id: CursorId) -> bool {
+        self.cursors.remove(&id).is_some()
+    }
+
+    /// Parse `sql` (a `SELECT` without a join) and register it for live
+    /// change notifications, returning its id and the receiving end of the
+    /// channel events arrive on. The channel immediately carries a `Columns`
+    /// event so a subscriber knows the result shape before the first
+    /// row-level event arrives.
+    pub fn subscribe(&mut self, sql: &str) -> Result<(SubscriptionId, mpsc::Receiver<QueryEvent>), String> {
+        let parsed = SQLParser::new().parse(sql);
+        let (table, condition) = match parsed.command {
+            CommandType::Select {
+                table,
+                condition,
+                joins,
+                ..
+            } => {
+                if !joins.is_empty() {
+                    return Err("Subscriptions do not support joins.".to_string());
+                }
+                (table, condition)
+            }
+            _ => return Err("Only SELECT statements can be subscribed to.".to_string()),
+        };
+
+        let rows = self.run_select(&table, condition.as_ref(), &[])?;
+        let mut headers: Vec<String> = rows
+            .first()
+            .map(|row| row.keys().cloned().collect())
+            .unwrap_or_default();
+        headers.sort();
+
+        let (sender, receiver) = mpsc::channel();
+        let _ = sender.send(QueryEvent::Columns(headers));
+
+        let id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        self.subscriptions.insert(
+            id,
+            Subscription {
+                table,
+                condition,
+                last_snapshot: rows,
+                sender,
+            },
+        );
+        Ok((id, receiver))
+    }
+
+    /// Cancel a subscription. Returns whether one existed. Dropping its
+    /// sender makes the receiving end's next `recv` return `Err`, so a
+    /// thread blocked reading its events notices the subscription ended
+    /// without having to poll.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.subscriptions.remove(&id).is_some()
+    }
+
+    /// Re-run every subscription registered against `table`, diff its new
+    /// match set against the one observed last time, and push the resulting
+    /// events down its channel. A subscriber that dropped its receiver is
+    /// pruned instead of erroring, the same as a real broadcast channel
+    /// would drop a lagging subscriber.
+    fn notify_subscriptions(&mut self, table: &str) {
+        let ids: Vec<SubscriptionId> = self
+            .subscriptions
+            .iter()
+            .filter(|(_, sub)| sub.table == table)
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in ids {
+            let (sub_table, condition) = {
+                let sub = &self.subscriptions[&id];
+                (sub.table.clone(), sub.condition.clone())
+            };
+            let current = match self.run_select(&sub_table, condition.as_ref(), &[]) {
+                Ok(rows) => rows,
+                Err(_) => continue,
+            };
+
+            let sub = self.subscriptions.get_mut(&id).unwrap();
+            let removed: Vec<HashMap<String, Value>> = sub
+                .last_snapshot
+                .iter()
+                .filter(|row| !current.contains(row))
+                .cloned()
+                .collect();
+            let added: Vec<HashMap<String, Value>> = current
+                .iter()
+                .filter(|row| !sub.last_snapshot.contains(row))
+                .cloned()
+                .collect();
+
+            let mut disconnected = false;
+            if !removed.is_empty() && removed.len() == added.len() {
+                for row in &added {
+                    if sub.sender.send(QueryEvent::Update(row.clone())).is_err() {
+                        disconnected = true;
+                    }
+                }
+            } else {
+                for row in &removed {
+                    if sub.sender.send(QueryEvent::Delete(row.clone())).is_err() {
+                        disconnected = true;
+                    }
+                }
+                for row in &added {
+                    if sub.sender.send(QueryEvent::Insert(row.clone())).is_err() {
+                        disconnected = true;
+                    }
+                }
+            }
+            sub.last_snapshot = current;
+
+            if disconnected {
+                self.subscriptions.remove(&id);
+            }
+        }
+    }
+
+    /// Select as seen from inside the open transaction: the committed rows
+    /// as of the snapshot, with the transaction's own buffered mutations
+    /// replayed on top. Joins are not supported under snapshot isolation yet
+    /// and fall back to reading committed state directly.
+    fn select_with_snapshot(
+        &self,
+        table: &str,
+        condition: Option<&Expr>,
+    ) -> Result<Vec<HashMap<String, Value>>, String> {
+        let storage = self.databases.get(&self.active_db).unwrap();
+        let mut rows = storage.select_rows(table, &["*".to_string()], None, &[])?;
+        let columns = storage.columns(table).unwrap_or_default();
+
+        if let Some(txn) = &self.txn {
+            for (_seq, mutation) in &txn.pending {
+                match mutation {
+                    PendingMutation::Insert { table: t, values } if t.as_str() == table => {
+                        let mut row = HashMap::new();
+                        for (col, val) in columns.iter().zip(values.iter()) {
+                            row.insert(col.clone(), val.clone());
+                        }
+                        rows.push(row);
+                    }
+                    PendingMutation::Update {
+                        table: t,
+                        assignments,
+                        condition: cond,
+                    } if t.as_str() == table => {
+                        for row in rows.iter_mut() {
+                            if Self::row_matches(row, cond.as_ref()) {
+                                for (key, value) in assignments {
+                                    row.insert(key.clone(), value.clone());
+                                }
+                            }
+                        }
+                    }
+                    PendingMutation::Delete { table: t, condition: cond } if t.as_str() == table => {
+                        rows.retain(|row| !Self::row_matches(row, cond.as_ref()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(cond) = condition {
+            rows.retain(|row| Self::row_matches(row, Some(cond)));
+        }
+
+        Ok(rows)
+    }
+
+    fn row_matches(row: &HashMap<String, Value>, condition: Option<&Expr>) -> bool {
+        match condition {
+            None => true,
+            Some(cond) => TableStore::eval_expr(row, cond),
+        }
+    }
+
+    /// The `__query_log` table's rows, oldest first, filtered by `condition`
+    /// the same way any other table's `WHERE` clause is.
+    fn audit_log_rows(&self, condition: Option<&Expr>) -> Vec<HashMap<String, Value>> {
+        self.audit_log
+            .iter()
+            .map(|entry| {
+                let mut row = HashMap::new();
+                row.insert(
+                    "timestamp".to_string(),
+                    Value::Integer(entry.timestamp_millis as i64),
+                );
+                row.insert("database".to_string(), Value::Text(entry.database.clone()));
+                row.insert("sql".to_string(), Value::Text(entry.sql.clone()));
+                row.insert(
+                    "success".to_string(),
+                    Value::Integer(if entry.success { 1 } else { 0 }),
+                );
+                row.insert(
+                    "error".to_string(),
+                    entry.error.clone().map(Value::Text).unwrap_or(Value::Null),
+                );
+                row.insert(
+                    "rows_affected".to_string(),
+                    entry
+                        .rows_affected
+                        .map(|n| Value::Integer(n as i64))
+                        .unwrap_or(Value::Null),
+                );
+                row
+            })
+            .filter(|row| Self::row_matches(row, condition))
+            .collect()
+    }
+
+    fn audit_log_columns() -> Vec<String> {
+        let mut names = vec![
+            "database".to_string(),
+            "error".to_string(),
+            "rows_affected".to_string(),
+            "sql".to_string(),
+            "success".to_string(),
+            "timestamp".to_string(),
+        ];
+        names.sort();
+        names
+    }
+
+    /// The most recent `n` audit-log entries, formatted the same way a
+    /// `SELECT`'s rows are, for the HTML "Recent activity" panel.
+    pub fn audit_log_tail(&self, n: usize) -> Vec<String> {
+        let rows = self.audit_log_rows(None);
+        let start = rows.len().saturating_sub(n);
+        let tail = rows[start..].to_vec();
+        let mut headers: Vec<String> = tail
+            .first()
+            .map(|row| row.keys().cloned().collect())
+            .unwrap_or_default();
+        headers.sort();
+        let typed = Self::rows_to_typed(&headers, tail);
+        self.format_rows(&typed)
+    }
+
+    /// Append an audit entry for `parsed`, truncating overlong SQL/error
+    /// text and evicting the oldest entry once the log is at capacity.
+    /// Skips blank input and reads of the audit log itself.
+    fn record_audit(&mut self, parsed: &ParsedCommand, result: &[String]) {
+        if matches!(parsed.command, CommandType::Empty) {
+            return;
+        }
+        if let CommandType::Select { table, .. } = &parsed.command {
+            if table == AUDIT_LOG_TABLE {
+                return;
+            }
+        }
+
+        let error = result
+            .first()
+            .and_then(|line| line.strip_prefix("Error: "))
+            .map(|msg| Self::truncate(msg, AUDIT_LOG_TEXT_LIMIT));
+        let rows_affected = Self::rows_affected_from_result(&parsed.command, result);
+
+        self.audit_log.push_back(AuditEntry {
+            timestamp_millis: Self::now_millis(),
+            database: self.active_db.clone(),
+            sql: Self::truncate(&parsed.raw, AUDIT_LOG_TEXT_LIMIT),
+            success: error.is_none(),
+            error,
+            rows_affected,
+        });
+
+        while self.audit_log.len() > AUDIT_LOG_MAX_ENTRIES {
+            self.audit_log.pop_front();
+        }
+    }
+
+    /// Best-effort rows-affected count read off a command's text reply,
+    /// e.g. the leading number in "3 row(s) updated."; `None` for commands
+    /// with nothing meaningful to count, or that errored.
+    fn rows_affected_from_result(command: &CommandType, result: &[String]) -> Option<usize> {
+        if result
+            .first()
+            .map(|line| line.starts_with("Error: "))
+            .unwrap_or(false)
+        {
+            return None;
+        }
+        match command {
+            CommandType::Insert { rows, .. } => Some(rows.len()),
+            CommandType::Update { .. } | CommandType::Delete { .. } => result
+                .first()
+                .and_then(|line| line.split_whitespace().next())
+                .and_then(|token| token.parse::<usize>().ok()),
+            CommandType::Select { .. } => {
+                if result.first().map(|s| s.as_str()) == Some("(no rows)") {
+                    Some(0)
+                } else {
+                    Some(result.len().saturating_sub(1))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn truncate(text: &str, max_chars: usize) -> String {
+        if text.chars().count() <= max_chars {
+            return text.to_string();
+        }
+        let mut truncated: String = text.chars().take(max_chars).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    fn now_millis() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+
     fn ensure_database(&mut self, name: &str) {
-        if !self.databases.contains_key(name) {
-            let path = self.data_dir.join(format!("{}.dat", name));
-            let pager = Pager::new(path, 4096);
-            self.databases
-                .insert(name.to_string(), BTreeStorage::new(Some(pager)));
+        self.ensure_database_with_engine(name, "btree");
+    }
+
+    /// Create the database with the requested engine if it doesn't already
+    /// exist. `btree` persists to a `.dat` file via `Pager`; `memory` keeps
+    /// table state only for the life of the process.
+    fn ensure_database_with_engine(&mut self, name: &str, engine: &str) {
+        if self.databases.contains_key(name) {
+            return;
         }
+        let storage: Box<dyn StorageEngine> = match engine {
+            "memory" => Box::new(MemoryStorage::new()),
+            _ => {
+                let path = self.data_dir.join(format!("{}.dat", name));
+                let pager = Pager::new(path, 4096);
+                Box::new(BTreeStorage::new(Some(pager)))
+            }
+        };
+        self.databases.insert(name.to_string(), storage);
+        self.open_wal(name);
     }
 
-    fn format_rows(&self, rows: &[HashMap<String, Value>], requested: &[String]) -> Vec<String> {
-        if rows.is_empty() {
-            return vec!["(no rows)".to_string()];
+    /// Open the database's WAL, replaying and re-applying any records left
+    /// behind by a crash before the last checkpoint, then truncate it so a
+    /// clean restart starts from an empty log. If the WAL is empty and the
+    /// storage engine came back with no tables at all (its `.dat` snapshot
+    /// is missing or was never written), fall back to replaying whatever
+    /// the LSM commit log still retains, as a last-resort recovery path.
+    fn open_wal(&mut self, name: &str) {
+        let wal_path = self.data_dir.join(format!("{}.wal", name));
+        let mut wal = match Wal::open(&wal_path) {
+            Ok(wal) => wal,
+            Err(_) => return,
+        };
+        let mut replayed_from_wal = false;
+        if let Ok(entries) = wal.replay() {
+            replayed_from_wal = !entries.is_empty();
+            if let Some(storage) = self.databases.get_mut(name) {
+                for entry in entries {
+                    Self::replay_entry(storage.as_mut(), &entry);
+                }
+            }
+            let _ = wal.checkpoint();
+        }
+        self.wals.insert(name.to_string(), wal);
+
+        if !replayed_from_wal {
+            let lsm_entries = self.lsm.entries_for(name);
+            if let Some(storage) = self.databases.get_mut(name) {
+                if storage.describe().is_empty() {
+                    for entry in lsm_entries {
+                        Self::replay_entry(storage.as_mut(), &entry);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Re-apply a single recovered WAL record to a freshly loaded storage
+    /// engine. Best-effort: a record referencing a table that no longer
+    /// exists (e.g. dropped after logging) is simply skipped.
+    fn replay_entry(storage: &mut dyn StorageEngine, entry: &LogEntry) {
+        let table = match entry.details.get("table").and_then(|v| v.as_str()) {
+            Some(table) => table.to_string(),
+            None => return,
+        };
+        if !storage.table_exists(&table) {
+            return;
+        }
+        match entry.command.as_str() {
+            "INSERT" => {
+                if let Some(rows) = entry
+                    .details
+                    .get("values")
+                    .and_then(|v| serde_json::from_value::<Vec<Vec<Value>>>(v.clone()).ok())
+                {
+                    let _ = storage.insert_rows(&table, rows);
+                }
+            }
+            "UPDATE" => {
+                let assignments = entry
+                    .details
+                    .get("assignments")
+                    .and_then(|v| serde_json::from_value::<HashMap<String, Value>>(v.clone()).ok())
+                    .unwrap_or_default();
+                let condition = entry
+                    .details
+                    .get("condition")
+                    .and_then(|v| serde_json::from_value::<Option<Expr>>(v.clone()).ok())
+                    .flatten();
+                let _ = storage.update_rows(&table, &assignments, condition.as_ref());
+            }
+            "DELETE" => {
+                let condition = entry
+                    .details
+                    .get("condition")
+                    .and_then(|v| serde_json::from_value::<Option<Expr>>(v.clone()).ok())
+                    .flatten();
+                let _ = storage.delete_rows(&table, condition.as_ref());
+            }
+            _ => {}
         }
+    }
+
+    /// Append a record to the active database's WAL and fsync it before the
+    /// mutation is allowed to touch `BTreeStorage`.
+    fn append_wal(&mut self, entry: &LogEntry) -> std::io::Result<()> {
+        match self.wals.get_mut(&self.active_db) {
+            Some(wal) => wal.append(entry),
+            None => Ok(()),
+        }
+    }
+
+    /// Map one `INSERT` value tuple to the table's full, positionally
+    /// ordered column list: with no column list the tuple is already
+    /// positional and passes through unchanged; with a column list, each
+    /// named column's value is placed at its table position and every
+    /// column the statement didn't mention is filled with `Value::Null`.
+    fn map_insert_row(
+        table_columns: &[String],
+        columns: Option<&[String]>,
+        row: &[Value],
+    ) -> Result<Vec<Value>, String> {
+        let names = match columns {
+            None => return Ok(row.to_vec()),
+            Some(names) => names,
+        };
+        if names.len() != row.len() {
+            return Err("Column list and VALUES count do not match".to_string());
+        }
+        for name in names {
+            if !table_columns.contains(name) {
+                return Err(format!("Unknown column '{}'", name));
+            }
+        }
+        Ok(table_columns
+            .iter()
+            .map(|col| {
+                names
+                    .iter()
+                    .position(|n| n == col)
+                    .map(|idx| row[idx].clone())
+                    .unwrap_or(Value::Null)
+            })
+            .collect())
+    }
+
+    /// Wrap rows already shaped to `headers` as `Row`s aligned to them.
+    /// Shared by the `Select` command path, `open_cursor`, and `query_rows`
+    /// so a row's column order always matches its headers.
+    fn rows_to_typed(headers: &[String], rows: Vec<HashMap<String, Value>>) -> Vec<Row> {
+        rows.iter()
+            .map(|row| Row {
+                columns: headers.to_vec(),
+                values: headers
+                    .iter()
+                    .map(|col| row.get(col).cloned().unwrap_or(Value::Null))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Apply a `SELECT`'s `GROUP BY`/aggregate projection, `ORDER BY`, and
+    /// `LIMIT`/`OFFSET` to its matched rows, resolving the column headers
+    /// the result carries along the way (expanding a lone `*` against the
+    /// first matched row, the same as before result columns existed).
+    fn shape_select(
+        rows: Vec<HashMap<String, Value>>,
+        columns: &[ResultColumn],
+        group_by: &[String],
+        order_by: &[OrderKey],
+        limit: Option<u64>,
+        offset: Option<u64>,
+    ) -> (Vec<String>, Vec<HashMap<String, Value>>) {
+        let has_aggregate = columns
+            .iter()
+            .any(|c| matches!(c, ResultColumn::Aggregate { .. }));
 
-        let headers = if requested.len() == 1 && requested[0] == "*" {
-            let mut keys: Vec<String> = rows[0].keys().cloned().collect();
+        let (headers, mut shaped) = if has_aggregate || !group_by.is_empty() {
+            Self::aggregate_rows(&rows, columns, group_by)
+        } else if columns.len() == 1 && columns[0].is_wildcard() {
+            let mut keys: Vec<String> = rows
+                .first()
+                .map(|row| row.keys().cloned().collect())
+                .unwrap_or_default();
             keys.sort();
-            keys
+            (keys, rows)
         } else {
-            requested.to_vec()
+            let headers: Vec<String> = columns.iter().map(|c| c.header()).collect();
+            let projected = rows
+                .into_iter()
+                .map(|row| {
+                    headers
+                        .iter()
+                        .map(|header| {
+                            let lookup = header.split('.').last().unwrap_or(header);
+                            (header.clone(), row.get(lookup).cloned().unwrap_or(Value::Null))
+                        })
+                        .collect::<HashMap<String, Value>>()
+                })
+                .collect();
+            (headers, projected)
         };
 
-        let mut lines = Vec::new();
-        lines.push(headers.join(" | "));
+        if !order_by.is_empty() {
+            shaped.sort_by(|a, b| Self::compare_by_order(a, b, order_by));
+        }
+
+        let start = offset.unwrap_or(0) as usize;
+        let shaped = if start >= shaped.len() {
+            Vec::new()
+        } else {
+            shaped.split_off(start)
+        };
+        let shaped = match limit {
+            Some(n) => shaped.into_iter().take(n as usize).collect(),
+            None => shaped,
+        };
+
+        (headers, shaped)
+    }
+
+    /// Multi-key `ORDER BY` comparator: ties on an earlier key fall through
+    /// to the next one, the way a standard SQL `ORDER BY a, b DESC` would.
+    fn compare_by_order(
+        a: &HashMap<String, Value>,
+        b: &HashMap<String, Value>,
+        order_by: &[OrderKey],
+    ) -> std::cmp::Ordering {
+        for key in order_by {
+            let av = a.get(&key.column).cloned().unwrap_or(Value::Null);
+            let bv = b.get(&key.column).cloned().unwrap_or(Value::Null);
+            let ordering = values_compare(&av, &bv);
+            let ordering = if key.descending { ordering.reverse() } else { ordering };
+            if ordering != std::cmp::Ordering::Equal {
+                return ordering;
+            }
+        }
+        std::cmp::Ordering::Equal
+    }
 
+    /// Group `rows` by `group_by` (the whole set as a single group when
+    /// empty) and compute each `ResultColumn::Aggregate`'s value per group.
+    /// A `Plain` column takes its value from the group's first row, which is
+    /// only meaningful when it names one of the grouping columns — the same
+    /// assumption most SQL engines make for an ungrouped column mixed into
+    /// an aggregate query.
+    fn aggregate_rows(
+        rows: &[HashMap<String, Value>],
+        columns: &[ResultColumn],
+        group_by: &[String],
+    ) -> (Vec<String>, Vec<HashMap<String, Value>>) {
+        let mut groups: Vec<(Vec<Value>, Vec<&HashMap<String, Value>>)> = Vec::new();
         for row in rows {
-            let values: Vec<String> = headers
+            let key: Vec<Value> = group_by
                 .iter()
-                .map(|col| {
-                    row.get(col)
-                        .map(|v| v.to_string())
-                        .unwrap_or_else(|| String::new())
-                })
+                .map(|c| row.get(c).cloned().unwrap_or(Value::Null))
                 .collect();
+            match groups.iter_mut().find(|(k, _)| k == &key) {
+                Some((_, members)) => members.push(row),
+                None => groups.push((key, vec![row])),
+            }
+        }
+        // An ungrouped aggregate (e.g. `SELECT COUNT(*) FROM t`) still
+        // produces one row even when no input row matched.
+        if groups.is_empty() && group_by.is_empty() {
+            groups.push((Vec::new(), Vec::new()));
+        }
+
+        let headers: Vec<String> = columns.iter().map(|c| c.header()).collect();
+        let shaped = groups
+            .into_iter()
+            .map(|(_, members)| {
+                let mut out = HashMap::new();
+                for column in columns {
+                    let value = match column {
+                        ResultColumn::Plain(name) => {
+                            let lookup = name.split('.').last().unwrap_or(name);
+                            members
+                                .first()
+                                .and_then(|row| row.get(lookup).cloned())
+                                .unwrap_or(Value::Null)
+                        }
+                        ResultColumn::Aggregate { func, arg } => Self::eval_aggregate(*func, arg, &members),
+                    };
+                    out.insert(column.header(), value);
+                }
+                out
+            })
+            .collect();
+
+        (headers, shaped)
+    }
+
+    /// Evaluate one aggregate function over a group's member rows.
+    fn eval_aggregate(func: AggregateFunc, arg: &str, members: &[&HashMap<String, Value>]) -> Value {
+        if func == AggregateFunc::Count {
+            if arg.trim() == "*" {
+                return Value::Integer(members.len() as i64);
+            }
+            let count = members
+                .iter()
+                .filter(|row| !matches!(row.get(arg), None | Some(Value::Null)))
+                .count();
+            return Value::Integer(count as i64);
+        }
+
+        let mut all_integer = true;
+        let numbers: Vec<f64> = members
+            .iter()
+            .filter_map(|row| match row.get(arg) {
+                Some(Value::Integer(i)) => Some(*i as f64),
+                Some(Value::Float(f)) => {
+                    all_integer = false;
+                    Some(*f)
+                }
+                _ => None,
+            })
+            .collect();
+        let to_value = |n: f64| if all_integer { Value::Integer(n as i64) } else { Value::Float(n) };
+
+        match func {
+            AggregateFunc::Sum => to_value(numbers.iter().sum()),
+            AggregateFunc::Avg => {
+                if numbers.is_empty() {
+                    Value::Null
+                } else {
+                    Value::Float(numbers.iter().sum::<f64>() / numbers.len() as f64)
+                }
+            }
+            AggregateFunc::Min => numbers
+                .iter()
+                .cloned()
+                .fold(None, |acc: Option<f64>, x| Some(acc.map_or(x, |a| a.min(x))))
+                .map(to_value)
+                .unwrap_or(Value::Null),
+            AggregateFunc::Max => numbers
+                .iter()
+                .cloned()
+                .fold(None, |acc: Option<f64>, x| Some(acc.map_or(x, |a| a.max(x))))
+                .map(to_value)
+                .unwrap_or(Value::Null),
+            AggregateFunc::Count => unreachable!("handled above"),
+        }
+    }
+
+    fn format_rows(&self, rows: &[Row]) -> Vec<String> {
+        if rows.is_empty() {
+            return vec!["(no rows)".to_string()];
+        }
+
+        let mut lines = Vec::new();
+        lines.push(rows[0].columns.join(" | "));
+
+        for row in rows {
+            let values: Vec<String> = row.values.iter().map(|v| v.to_string()).collect();
             lines.push(values.join(" | "));
         }
 
         lines
     }
 
+    /// Run a `SELECT` and return its rows typed rather than formatted to
+    /// text, for callers that want positional decoding via `FromRow` (the
+    /// JSON query endpoint) instead of the `execute`/`format_rows` text path.
+    pub fn query_rows(&self, parsed: &ParsedCommand) -> Result<(Vec<String>, Vec<Row>), String> {
+        let (table, columns, condition, joins, order_by, limit, offset, group_by) = match &parsed.command {
+            CommandType::Select {
+                table,
+                columns,
+                condition,
+                joins,
+                order_by,
+                limit,
+                offset,
+                group_by,
+            } => (
+                table,
+                columns,
+                condition.as_ref(),
+                joins,
+                order_by,
+                *limit,
+                *offset,
+                group_by,
+            ),
+            _ => return Err("Only SELECT statements return rows.".to_string()),
+        };
+        let rows = self.run_select(table, condition, joins)?;
+        let (headers, shaped) = Self::shape_select(rows, columns, group_by, order_by, limit, offset);
+        let typed_rows = Self::rows_to_typed(&headers, shaped);
+        Ok((headers, typed_rows))
+    }
+
+    /// Column names a `Select` command's result would carry, so a front end
+    /// (e.g. the Postgres wire protocol's `RowDescription`) can announce
+    /// them before or alongside the formatted rows `execute` returns.
+    /// `None` for every other command, which has no row shape to describe.
+    pub fn describe_result(&self, parsed: &ParsedCommand) -> Option<Vec<String>> {
+        match &parsed.command {
+            CommandType::Select {
+                table,
+                columns,
+                joins,
+                ..
+            } => {
+                if table == AUDIT_LOG_TABLE {
+                    return Some(if columns.len() == 1 && columns[0].is_wildcard() {
+                        Self::audit_log_columns()
+                    } else {
+                        columns.iter().map(|c| c.header()).collect()
+                    });
+                }
+                let storage = self.databases.get(&self.active_db)?;
+                if columns.len() == 1 && columns[0].is_wildcard() {
+                    let mut names = storage.columns(table)?;
+                    if !joins.is_empty() {
+                        names = names.into_iter().map(|c| format!("{}.{}", table, c)).collect();
+                        for join_info in joins {
+                            let join_cols = storage.columns(&join_info.table)?;
+                            names.extend(
+                                join_cols
+                                    .into_iter()
+                                    .map(|c| format!("{}.{}", join_info.table, c)),
+                            );
+                        }
+                    }
+                    // `shape_select` emits wildcard headers in sorted order.
+                    names.sort();
+                    Some(names)
+                } else {
+                    Some(columns.iter().map(|c| c.header()).collect())
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Register an already-parsed statement for later `bind`/`execute_prepared`
+    /// calls, returning the id it is registered under. The statement's
+    /// `Value::Placeholder` slots are recorded as-is; parsing only happens once.
+    pub fn prepare(&mut self, parsed: ParsedCommand) -> StatementId {
+        let id = self.next_statement_id;
+        self.next_statement_id += 1;
+        self.prepared.insert(id, parsed);
+        id
+    }
+
+    /// Column names `id`'s result would carry, same as `describe_result` but
+    /// looked up from a previously prepared statement instead of a fresh parse.
+    pub fn describe_prepared(&self, id: StatementId) -> Option<Vec<String>> {
+        let parsed = self.prepared.get(&id)?;
+        self.describe_result(parsed)
+    }
+
+    /// The number of parameters `id`'s statement expects: one more than its
+    /// highest `?`/`$N` placeholder index (i.e. the largest `N`).
+    pub fn prepared_param_count(&self, id: StatementId) -> Option<usize> {
+        self.prepared.get(&id).map(Self::placeholder_count)
+    }
+
+    /// Validate `params` against the statement's placeholder count and store
+    /// them for the next `execute_prepared(id)` call.
+    pub fn bind(&mut self, id: StatementId, params: Vec<Value>) -> Result<(), String> {
+        let expected = self
+            .prepared
+            .get(&id)
+            .map(Self::placeholder_count)
+            .ok_or_else(|| format!("Unknown prepared statement {}", id))?;
+        if params.len() != expected {
+            return Err(format!(
+                "Statement {} expects {} parameter(s), got {}",
+                id,
+                expected,
+                params.len()
+            ));
+        }
+        self.bound_params.insert(id, params);
+        Ok(())
+    }
+
+    /// Substitute the most recently bound parameters into `id`'s statement
+    /// and run it, same as a fresh `execute` call on the substituted form.
+    pub fn execute_prepared(&mut self, id: StatementId) -> Result<Vec<String>, String> {
+        let template = self
+            .prepared
+            .get(&id)
+            .ok_or_else(|| format!("Unknown prepared statement {}", id))?
+            .clone();
+        let params = self.bound_params.get(&id).cloned().unwrap_or_default();
+        let bound = Self::substitute(&template, &params);
+        Ok(self.execute(&bound))
+    }
+
+    fn placeholder_count(parsed: &ParsedCommand) -> usize {
+        let mut max = 0usize;
+        match &parsed.command {
+            CommandType::Insert { rows, .. } => {
+                for row in rows {
+                    for value in row {
+                        Self::count_placeholder(value, &mut max);
+                    }
+                }
+            }
+            CommandType::Update {
+                assignments,
+                condition,
+                ..
+            } => {
+                for value in assignments.values() {
+                    Self::count_placeholder(value, &mut max);
+                }
+                Self::count_placeholder_condition(condition, &mut max);
+            }
+            CommandType::Delete { condition, .. } => {
+                Self::count_placeholder_condition(condition, &mut max);
+            }
+            CommandType::Select { condition, joins, .. } => {
+                Self::count_placeholder_condition(condition, &mut max);
+                for join in joins {
+                    Self::count_placeholder_expr(&join.on, &mut max);
+                }
+            }
+            _ => {}
+        }
+        max
+    }
+
+    fn count_placeholder(value: &Value, max: &mut usize) {
+        if let Value::Placeholder(n) = value {
+            *max = (*max).max(n + 1);
+        }
+    }
+
+    fn count_placeholder_condition(condition: &Option<Expr>, max: &mut usize) {
+        if let Some(expr) = condition {
+            Self::count_placeholder_expr(expr, max);
+        }
+    }
+
+    fn count_placeholder_expr(expr: &Expr, max: &mut usize) {
+        match expr {
+            Expr::Compare { value, .. } => Self::count_placeholder(value, max),
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                Self::count_placeholder_expr(left, max);
+                Self::count_placeholder_expr(right, max);
+            }
+            Expr::Not(inner) => Self::count_placeholder_expr(inner, max),
+            Expr::IsNull(_) => {}
+        }
+    }
+
+    fn substitute_value(value: &Value, params: &[Value]) -> Value {
+        match value {
+            Value::Placeholder(n) => params.get(*n).cloned().unwrap_or(Value::Null),
+            other => other.clone(),
+        }
+    }
+
+    fn substitute_condition(expr: &Expr, params: &[Value]) -> Expr {
+        match expr {
+            Expr::Compare { column, op, value } => Expr::Compare {
+                column: column.clone(),
+                op: *op,
+                value: Self::substitute_value(value, params),
+            },
+            Expr::And(left, right) => Expr::And(
+                Box::new(Self::substitute_condition(left, params)),
+                Box::new(Self::substitute_condition(right, params)),
+            ),
+            Expr::Or(left, right) => Expr::Or(
+                Box::new(Self::substitute_condition(left, params)),
+                Box::new(Self::substitute_condition(right, params)),
+            ),
+            Expr::Not(inner) => Expr::Not(Box::new(Self::substitute_condition(inner, params))),
+            Expr::IsNull(column) => Expr::IsNull(column.clone()),
+        }
+    }
+
+    /// Replace every `Value::Placeholder` in a parsed statement with the
+    /// corresponding bound parameter.
+    fn substitute(parsed: &ParsedCommand, params: &[Value]) -> ParsedCommand {
+        let command = match &parsed.command {
+            CommandType::Insert { table, columns, rows } => CommandType::Insert {
+                table: table.clone(),
+                columns: columns.clone(),
+                rows: rows
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|v| Self::substitute_value(v, params))
+                            .collect()
+                    })
+                    .collect(),
+            },
+            CommandType::Update {
+                table,
+                assignments,
+                condition,
+            } => CommandType::Update {
+                table: table.clone(),
+                assignments: assignments
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Self::substitute_value(v, params)))
+                    .collect(),
+                condition: condition.as_ref().map(|c| Self::substitute_condition(c, params)),
+            },
+            CommandType::Delete { table, condition } => CommandType::Delete {
+                table: table.clone(),
+                condition: condition.as_ref().map(|c| Self::substitute_condition(c, params)),
+            },
+            CommandType::Select {
+                table,
+                columns,
+                condition,
+                joins,
+                order_by,
+                limit,
+                offset,
+                group_by,
+            } => CommandType::Select {
+                table: table.clone(),
+                columns: columns.clone(),
+                condition: condition.as_ref().map(|c| Self::substitute_condition(c, params)),
+                joins: joins
+                    .iter()
+                    .map(|j| JoinInfo {
+                        table: j.table.clone(),
+                        join_type: j.join_type,
+                        on: Self::substitute_condition(&j.on, params),
+                    })
+                    .collect(),
+                order_by: order_by.clone(),
+                limit: *limit,
+                offset: *offset,
+                group_by: group_by.clone(),
+            },
+            other => other.clone(),
+        };
+        ParsedCommand {
+            command,
+            raw: parsed.raw.clone(),
+        }
+    }
+
     pub fn describe(&self) -> HashMap<String, HashMap<String, serde_json::Value>> {
         let mut result = HashMap::new();
         for (db_name, storage) in &self.databases {
@@ -261,6 +1835,16 @@ impl SQLExecutor {
         &self.active_db
     }
 
+    /// Switch the active database without going through a `USE` statement,
+    /// e.g. to restore a caller's session-scoped selection before running
+    /// its query. A name that doesn't exist yet is ignored, same as `USE`
+    /// leaves the active database unchanged when it can't find a match.
+    pub fn set_active_database(&mut self, name: &str) {
+        if self.databases.contains_key(name) {
+            self.active_db = name.to_string();
+        }
+    }
+
     pub fn databases(&self) -> Vec<String> {
         let mut names: Vec<String> = self.databases.keys().cloned().collect();
         names.sort();
@@ -279,9 +1863,11 @@ impl SQLExecutor {
                     continue;
                 }
                 if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    let name = stem.to_lowercase();
                     let pager = Pager::new(path.clone(), 4096);
                     self.databases
-                        .insert(stem.to_lowercase(), BTreeStorage::new(Some(pager)));
+                        .insert(name.clone(), Box::new(BTreeStorage::new(Some(pager))));
+                    self.open_wal(&name);
                 }
             }
         }
@@ -293,3 +1879,83 @@ impl Default for SQLExecutor {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::SQLParser;
+    use std::sync::atomic::{AtomicU32, Ordering as AtomicOrdering};
+
+    static TEST_DB_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    /// A fresh `SQLExecutor` with its own `memory`-engine database and a
+    /// single-column `t` table, so each test runs against isolated state
+    /// without touching the shared on-disk `.dat` files other tests use.
+    fn fresh_executor() -> (SQLExecutor, SQLParser) {
+        let mut executor = SQLExecutor::new();
+        let parser = SQLParser::new();
+        let db_name = format!(
+            "mvcc_test_{}",
+            TEST_DB_COUNTER.fetch_add(1, AtomicOrdering::SeqCst)
+        );
+        executor.execute(&parser.parse(&format!("CREATE DATABASE {} ENGINE MEMORY", db_name)));
+        executor.execute(&parser.parse("CREATE TABLE t (a)"));
+        (executor, parser)
+    }
+
+    fn select_t_values(executor: &SQLExecutor, parser: &SQLParser) -> Vec<Value> {
+        let (_, rows) = executor
+            .query_rows(&parser.parse("SELECT * FROM t"))
+            .unwrap();
+        rows.iter().map(|r| r.values[0].clone()).collect()
+    }
+
+    #[test]
+    fn uncommitted_insert_is_visible_within_its_own_transaction() {
+        let (mut executor, parser) = fresh_executor();
+        executor.execute(&parser.parse("INSERT INTO t VALUES (1)"));
+        executor.execute(&parser.parse("BEGIN"));
+        executor.execute(&parser.parse("INSERT INTO t VALUES (2)"));
+
+        let values = select_t_values(&executor, &parser);
+        assert_eq!(values, vec![Value::Integer(1), Value::Integer(2)]);
+    }
+
+    #[test]
+    fn rollback_discards_the_transaction_snapshot_entirely() {
+        let (mut executor, parser) = fresh_executor();
+        executor.execute(&parser.parse("INSERT INTO t VALUES (1)"));
+        executor.execute(&parser.parse("BEGIN"));
+        executor.execute(&parser.parse("INSERT INTO t VALUES (2)"));
+        executor.execute(&parser.parse("ROLLBACK"));
+
+        let values = select_t_values(&executor, &parser);
+        assert_eq!(values, vec![Value::Integer(1)]);
+    }
+
+    #[test]
+    fn commit_applies_buffered_mutations_so_they_persist_outside_the_transaction() {
+        let (mut executor, parser) = fresh_executor();
+        executor.execute(&parser.parse("BEGIN"));
+        executor.execute(&parser.parse("INSERT INTO t VALUES (1)"));
+        executor.execute(&parser.parse("INSERT INTO t VALUES (2)"));
+        let result = executor.execute(&parser.parse("COMMIT"));
+
+        assert_eq!(result, vec!["Committed transaction: 2 mutation(s) applied."]);
+        assert!(executor.txn.is_none());
+        let values = select_t_values(&executor, &parser);
+        assert_eq!(values, vec![Value::Integer(1), Value::Integer(2)]);
+    }
+
+    #[test]
+    fn a_second_begin_is_rejected_while_a_transaction_is_already_open() {
+        let (mut executor, parser) = fresh_executor();
+        executor.execute(&parser.parse("BEGIN"));
+        let result = executor.execute(&parser.parse("BEGIN"));
+
+        assert_eq!(
+            result,
+            vec!["A transaction is already in progress (snapshot seq 0)."]
+        );
+    }
+}
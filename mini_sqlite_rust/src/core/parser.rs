@@ -9,6 +9,20 @@ pub enum Value {
     Float(f64),
     Text(String),
     Null,
+    /// A `$N` (or normalized bare `?`) parameter placeholder recorded while
+    /// parsing a prepared statement, holding its 0-indexed bind position.
+    /// Substituted by `SQLExecutor::bind`/`execute_prepared`. Never appears
+    /// in stored row data — only ever in a `ParsedCommand` awaiting bind.
+    Placeholder(usize),
+    /// A `?1`/`:name`/`$name` parameter reference, substituted by
+    /// `ParsedCommand::bind`/`bind_named` instead of the executor's
+    /// placeholder flow. See [`ParamRef`].
+    Param(ParamRef),
+    /// A reference to another (possibly `table.column`-qualified) column,
+    /// only ever appearing in the value position of an `Expr::Compare` —
+    /// e.g. a join's `ON a.x = b.x`. Resolved against the row under
+    /// evaluation instead of being a literal in its own right.
+    Column(String),
 }
 
 impl std::fmt::Display for Value {
@@ -18,23 +32,166 @@ impl std::fmt::Display for Value {
             Value::Float(fl) => write!(f, "{}", fl),
             Value::Text(s) => write!(f, "{}", s),
             Value::Null => write!(f, "NULL"),
+            Value::Placeholder(n) => write!(f, "${}", n + 1),
+            Value::Param(param) => write!(f, "{}", param),
+            Value::Column(name) => write!(f, "{}", name),
         }
     }
 }
 
+impl Value {
+    /// Render as a plain JSON scalar (a number, string, or null) rather than
+    /// the tagged enum shape `#[derive(Serialize)]` would produce, for APIs
+    /// that want ordinary JSON values in a row rather than `{"Integer":5}`.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Integer(i) => serde_json::json!(i),
+            Value::Float(fl) => serde_json::json!(fl),
+            Value::Text(s) => serde_json::json!(s),
+            Value::Null => serde_json::Value::Null,
+            Value::Placeholder(n) => serde_json::json!(format!("${}", n + 1)),
+            Value::Param(param) => serde_json::json!(param.to_string()),
+            Value::Column(name) => serde_json::json!(name),
+        }
+    }
+}
+
+/// A positional (`?1`, 0-indexed internally) or named (`:name`/`$name`)
+/// reference to a value supplied later by `ParsedCommand::bind`/`bind_named`,
+/// mirroring rusqlite's positional/named binding model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ParamRef {
+    Positional(usize),
+    Named(String),
+}
+
+impl std::fmt::Display for ParamRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParamRef::Positional(n) => write!(f, "?{}", n + 1),
+            ParamRef::Named(name) => write!(f, ":{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Like,
+}
+
+/// A boolean `WHERE` predicate. `Compare` is the only leaf; everything else
+/// combines sub-expressions, so `age >= 18 AND (name LIKE 'A%' OR active = 1)`
+/// parses into a small tree instead of a single flat condition. `BETWEEN x
+/// AND y` has no dedicated variant — `parse_condition` desugars it into
+/// `And(Compare(Ge, x), Compare(Le, y))`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Condition {
-    pub column: String,
-    pub value: Value,
+pub enum Expr {
+    Compare {
+        column: String,
+        op: CompareOp,
+        value: Value,
+    },
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    IsNull(String),
 }
 
+/// Which side(s) of a join must keep their rows even without a match:
+/// `Inner` drops unmatched rows from both sides, `Left`/`Right` NULL-pad the
+/// other side's columns for a row that didn't match, and `Full` does both.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum JoinType {
+    Inner,
+    Left,
+    Right,
+    Full,
+}
+
+/// One `JOIN <table> ON <condition>` clause, chained onto a `Select`'s base
+/// table (or onto the previous `JoinInfo` in the chain). `on` is a full
+/// boolean expression — not just a single `left.col = right.col` equality —
+/// parsed by the same condition parser `WHERE` uses, so `AND`-chained
+/// multi-column join keys work the same way a compound `WHERE` does.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JoinInfo {
     pub table: String,
-    pub left_table: String,
-    pub left_column: String,
-    pub right_table: String,
-    pub right_column: String,
+    pub join_type: JoinType,
+    pub on: Expr,
+}
+
+/// A single `ORDER BY` key: the column to sort on and whether it's `DESC`
+/// (default `ASC`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderKey {
+    pub column: String,
+    pub descending: bool,
+}
+
+/// An aggregate function recognized in a `SELECT`'s result column list,
+/// e.g. `COUNT(*)` or `AVG(price)`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AggregateFunc {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunc {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "COUNT" => Some(AggregateFunc::Count),
+            "SUM" => Some(AggregateFunc::Sum),
+            "AVG" => Some(AggregateFunc::Avg),
+            "MIN" => Some(AggregateFunc::Min),
+            "MAX" => Some(AggregateFunc::Max),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AggregateFunc {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            AggregateFunc::Count => "COUNT",
+            AggregateFunc::Sum => "SUM",
+            AggregateFunc::Avg => "AVG",
+            AggregateFunc::Min => "MIN",
+            AggregateFunc::Max => "MAX",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One entry of a `SELECT`'s result column list: either a plain column (or
+/// `*`) or an aggregate function applied to one, e.g. `SUM(amount)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ResultColumn {
+    Plain(String),
+    Aggregate { func: AggregateFunc, arg: String },
+}
+
+impl ResultColumn {
+    /// The header text this column contributes to a result set, e.g. `id`
+    /// or `COUNT(*)`.
+    pub fn header(&self) -> String {
+        match self {
+            ResultColumn::Plain(name) => name.clone(),
+            ResultColumn::Aggregate { func, arg } => format!("{}({})", func, arg),
+        }
+    }
+
+    pub fn is_wildcard(&self) -> bool {
+        matches!(self, ResultColumn::Plain(name) if name == "*")
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +205,7 @@ pub enum CommandType {
     Empty,
     CreateDatabase {
         name: String,
+        engine: String,
     },
     AlterDatabase {
         name: String,
@@ -76,24 +234,38 @@ pub enum CommandType {
     },
     Insert {
         table: String,
-        values: Vec<Value>,
+        /// The target columns named by an `INSERT INTO t (a, b) VALUES ...`
+        /// column list; `None` when the statement gave no list, leaving
+        /// each row's values positional in the table's declared column
+        /// order.
+        columns: Option<Vec<String>>,
+        /// One entry per `VALUES` tuple; every tuple has the same arity.
+        rows: Vec<Vec<Value>>,
     },
     Update {
         table: String,
         assignments: HashMap<String, Value>,
-        condition: Option<Condition>,
+        condition: Option<Expr>,
     },
     Delete {
         table: String,
-        condition: Option<Condition>,
+        condition: Option<Expr>,
     },
     Select {
         table: String,
-        columns: Vec<String>,
-        condition: Option<Condition>,
-        join: Option<JoinInfo>,
+        columns: Vec<ResultColumn>,
+        condition: Option<Expr>,
+        /// Zero or more chained `JOIN`s, applied in order against the
+        /// accumulated result of the base table and every prior join.
+        joins: Vec<JoinInfo>,
+        order_by: Vec<OrderKey>,
+        limit: Option<u64>,
+        offset: Option<u64>,
+        group_by: Vec<String>,
     },
     Commit,
+    Begin,
+    Rollback,
     Unknown,
 }
 
@@ -103,6 +275,129 @@ pub struct ParsedCommand {
     pub raw: String,
 }
 
+impl ParsedCommand {
+    /// Replace every `Value::Param(ParamRef::Positional(n))` in this
+    /// statement with `params[n]`; a reference with no matching entry (or a
+    /// named one) is left as-is. Lets a `PREPARE`d statement be `EXECUTE`d
+    /// against different positional inputs without re-parsing the SQL, and
+    /// without re-escaping the values back into SQL text.
+    pub fn bind(&self, params: &[Value]) -> ParsedCommand {
+        self.substitute(&|value| match value {
+            Value::Param(ParamRef::Positional(n)) => params.get(*n).cloned(),
+            _ => None,
+        })
+    }
+
+    /// Same as `bind`, but resolves `Value::Param(ParamRef::Named(name))`
+    /// references against a name-keyed map instead of position.
+    pub fn bind_named(&self, params: &HashMap<String, Value>) -> ParsedCommand {
+        self.substitute(&|value| match value {
+            Value::Param(ParamRef::Named(name)) => params.get(name).cloned(),
+            _ => None,
+        })
+    }
+
+    fn substitute(&self, resolve: &dyn Fn(&Value) -> Option<Value>) -> ParsedCommand {
+        ParsedCommand {
+            command: Self::substitute_command(&self.command, resolve),
+            raw: self.raw.clone(),
+        }
+    }
+
+    fn substitute_command(command: &CommandType, resolve: &dyn Fn(&Value) -> Option<Value>) -> CommandType {
+        let sub_value = |v: &Value| resolve(v).unwrap_or_else(|| v.clone());
+        match command {
+            CommandType::Insert { table, columns, rows } => CommandType::Insert {
+                table: table.clone(),
+                columns: columns.clone(),
+                rows: rows
+                    .iter()
+                    .map(|row| row.iter().map(sub_value).collect())
+                    .collect(),
+            },
+            CommandType::Update {
+                table,
+                assignments,
+                condition,
+            } => CommandType::Update {
+                table: table.clone(),
+                assignments: assignments.iter().map(|(k, v)| (k.clone(), sub_value(v))).collect(),
+                condition: condition.as_ref().map(|c| Self::substitute_expr(c, resolve)),
+            },
+            CommandType::Delete { table, condition } => CommandType::Delete {
+                table: table.clone(),
+                condition: condition.as_ref().map(|c| Self::substitute_expr(c, resolve)),
+            },
+            CommandType::Select {
+                table,
+                columns,
+                condition,
+                joins,
+                order_by,
+                limit,
+                offset,
+                group_by,
+            } => CommandType::Select {
+                table: table.clone(),
+                columns: columns.clone(),
+                condition: condition.as_ref().map(|c| Self::substitute_expr(c, resolve)),
+                joins: joins
+                    .iter()
+                    .map(|j| JoinInfo {
+                        table: j.table.clone(),
+                        join_type: j.join_type,
+                        on: Self::substitute_expr(&j.on, resolve),
+                    })
+                    .collect(),
+                order_by: order_by.clone(),
+                limit: *limit,
+                offset: *offset,
+                group_by: group_by.clone(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    fn substitute_expr(expr: &Expr, resolve: &dyn Fn(&Value) -> Option<Value>) -> Expr {
+        match expr {
+            Expr::Compare { column, op, value } => Expr::Compare {
+                column: column.clone(),
+                op: *op,
+                value: resolve(value).unwrap_or_else(|| value.clone()),
+            },
+            Expr::And(left, right) => Expr::And(
+                Box::new(Self::substitute_expr(left, resolve)),
+                Box::new(Self::substitute_expr(right, resolve)),
+            ),
+            Expr::Or(left, right) => Expr::Or(
+                Box::new(Self::substitute_expr(left, resolve)),
+                Box::new(Self::substitute_expr(right, resolve)),
+            ),
+            Expr::Not(inner) => Expr::Not(Box::new(Self::substitute_expr(inner, resolve))),
+            Expr::IsNull(column) => Expr::IsNull(column.clone()),
+        }
+    }
+}
+
+/// Why `SQLParser::normalize` couldn't canonicalize an input string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The input didn't parse into any command this parser recognizes.
+    Unrecognized,
+    /// The input holds more than one `;`-separated statement, so it has no
+    /// single canonical form.
+    MultipleStatements,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::Unrecognized => write!(f, "statement not recognized"),
+            ParseError::MultipleStatements => write!(f, "input contains more than one statement"),
+        }
+    }
+}
+
 pub struct SQLParser;
 
 impl SQLParser {
@@ -110,6 +405,252 @@ impl SQLParser {
         SQLParser
     }
 
+    /// Produce a single canonical text form of `query`: uppercase keywords,
+    /// lowercase identifiers, collapsed whitespace, literals rewritten into
+    /// a stable shape (integers without leading zeros, text single-quoted).
+    /// Two syntactically different spellings of the same statement map to
+    /// the same output, making this suitable as a cache/dedup key. Rejects
+    /// input that holds more than one statement or that doesn't parse.
+    pub fn normalize(&self, query: &str) -> Result<String, ParseError> {
+        let trimmed = query.trim().trim_end_matches(';').trim();
+        if Self::has_multiple_statements(trimmed) {
+            return Err(ParseError::MultipleStatements);
+        }
+        let parsed = self.parse(trimmed);
+        Self::render_command(&parsed.command).ok_or(ParseError::Unrecognized)
+    }
+
+    /// Whether `text` holds a `;` outside of a quoted string, i.e. more than
+    /// one statement once the (already-stripped) trailing terminator is
+    /// accounted for.
+    fn has_multiple_statements(text: &str) -> bool {
+        let mut quote: Option<char> = None;
+        for ch in text.chars() {
+            match quote {
+                Some(q) => {
+                    if ch == q {
+                        quote = None;
+                    }
+                }
+                None => match ch {
+                    '\'' | '"' => quote = Some(ch),
+                    ';' => return true,
+                    _ => {}
+                },
+            }
+        }
+        false
+    }
+
+    /// Split `text` into individual statements on `;` outside of a quoted
+    /// string, trimming each and dropping empty ones. Unlike `parse`, which
+    /// treats its whole input as a single statement, this lets a caller (e.g.
+    /// the web form) submit a whole `BEGIN; INSERT ...; COMMIT;` batch and
+    /// run each statement against the engine in order.
+    pub fn split_statements(&self, text: &str) -> Vec<String> {
+        let mut statements = Vec::new();
+        let mut current = String::new();
+        let mut quote: Option<char> = None;
+        for ch in text.chars() {
+            match quote {
+                Some(q) => {
+                    current.push(ch);
+                    if ch == q {
+                        quote = None;
+                    }
+                }
+                None => match ch {
+                    '\'' | '"' => {
+                        quote = Some(ch);
+                        current.push(ch);
+                    }
+                    ';' => {
+                        let statement = current.trim().to_string();
+                        current.clear();
+                        if !statement.is_empty() {
+                            statements.push(statement);
+                        }
+                    }
+                    _ => current.push(ch),
+                },
+            }
+        }
+        let trailing = current.trim().to_string();
+        if !trailing.is_empty() {
+            statements.push(trailing);
+        }
+        statements
+    }
+
+    /// Render a parsed command back into canonical SQL text. `None` for
+    /// `CommandType::Unknown`, which has no canonical form.
+    fn render_command(command: &CommandType) -> Option<String> {
+        Some(match command {
+            CommandType::Empty => String::new(),
+            CommandType::Commit => "COMMIT".to_string(),
+            CommandType::Begin => "BEGIN".to_string(),
+            CommandType::Rollback => "ROLLBACK".to_string(),
+            CommandType::CreateDatabase { name, engine } => {
+                format!("CREATE DATABASE {} ENGINE {}", name, engine.to_uppercase())
+            }
+            CommandType::AlterDatabase { name } => format!("ALTER DATABASE {}", name),
+            CommandType::UseDatabase { name } => format!("USE {}", name),
+            CommandType::CreateTable { table, columns } => {
+                let cols: Vec<String> = columns
+                    .iter()
+                    .map(|c| format!("{} {}", c.name, c.col_type.to_uppercase()))
+                    .collect();
+                format!("CREATE TABLE {} ({})", table, cols.join(", "))
+            }
+            CommandType::AlterTable { table, column } => format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table,
+                column.name,
+                column.col_type.to_uppercase()
+            ),
+            CommandType::DropTable { table } => format!("DROP TABLE {}", table),
+            CommandType::CreateIndex { table, column } => format!("CREATE INDEX {} {}", table, column),
+            CommandType::DropIndex { table, column } => format!("DROP INDEX {} {}", table, column),
+            CommandType::Insert { table, columns, rows } => {
+                let tuples: Vec<String> = rows
+                    .iter()
+                    .map(|row| {
+                        let vals: Vec<String> = row.iter().map(Self::render_value).collect();
+                        format!("({})", vals.join(", "))
+                    })
+                    .collect();
+                match columns {
+                    Some(cols) => format!(
+                        "INSERT INTO {} ({}) VALUES {}",
+                        table,
+                        cols.join(", "),
+                        tuples.join(", ")
+                    ),
+                    None => format!("INSERT INTO {} VALUES {}", table, tuples.join(", ")),
+                }
+            }
+            CommandType::Update {
+                table,
+                assignments,
+                condition,
+            } => {
+                let mut keys: Vec<&String> = assignments.keys().collect();
+                keys.sort();
+                let sets: Vec<String> = keys
+                    .iter()
+                    .map(|k| format!("{} = {}", k, Self::render_value(&assignments[*k])))
+                    .collect();
+                let mut text = format!("UPDATE {} SET {}", table, sets.join(", "));
+                if let Some(cond) = condition {
+                    text.push_str(&format!(" WHERE {}", Self::render_expr(cond)));
+                }
+                text
+            }
+            CommandType::Delete { table, condition } => {
+                let mut text = format!("DELETE FROM {}", table);
+                if let Some(cond) = condition {
+                    text.push_str(&format!(" WHERE {}", Self::render_expr(cond)));
+                }
+                text
+            }
+            CommandType::Select {
+                table,
+                columns,
+                condition,
+                joins,
+                order_by,
+                limit,
+                offset,
+                group_by,
+            } => {
+                let cols: Vec<String> = columns.iter().map(|c| c.header()).collect();
+                let mut text = format!("SELECT {} FROM {}", cols.join(", "), table);
+                for j in joins {
+                    text.push_str(&format!(
+                        " {} JOIN {} ON {}",
+                        Self::render_join_type(j.join_type),
+                        j.table,
+                        Self::render_expr(&j.on)
+                    ));
+                }
+                if let Some(cond) = condition {
+                    text.push_str(&format!(" WHERE {}", Self::render_expr(cond)));
+                }
+                if !group_by.is_empty() {
+                    text.push_str(&format!(" GROUP BY {}", group_by.join(", ")));
+                }
+                if !order_by.is_empty() {
+                    let keys: Vec<String> = order_by
+                        .iter()
+                        .map(|k| {
+                            if k.descending {
+                                format!("{} DESC", k.column)
+                            } else {
+                                k.column.clone()
+                            }
+                        })
+                        .collect();
+                    text.push_str(&format!(" ORDER BY {}", keys.join(", ")));
+                }
+                if let Some(n) = limit {
+                    text.push_str(&format!(" LIMIT {}", n));
+                    if let Some(o) = offset {
+                        text.push_str(&format!(" OFFSET {}", o));
+                    }
+                }
+                text
+            }
+            CommandType::Unknown => return None,
+        })
+    }
+
+    /// Render a single value into its canonical literal text.
+    fn render_value(value: &Value) -> String {
+        match value {
+            Value::Integer(i) => i.to_string(),
+            Value::Float(f) => f.to_string(),
+            Value::Text(s) => format!("'{}'", s),
+            Value::Null => "NULL".to_string(),
+            Value::Placeholder(n) => format!("${}", n + 1),
+            Value::Param(p) => p.to_string(),
+            Value::Column(name) => name.clone(),
+        }
+    }
+
+    /// Render a `WHERE` predicate tree back into canonical SQL text.
+    fn render_expr(expr: &Expr) -> String {
+        match expr {
+            Expr::Compare { column, op, value } => {
+                format!("{} {} {}", column, Self::render_op(*op), Self::render_value(value))
+            }
+            Expr::And(left, right) => format!("({} AND {})", Self::render_expr(left), Self::render_expr(right)),
+            Expr::Or(left, right) => format!("({} OR {})", Self::render_expr(left), Self::render_expr(right)),
+            Expr::Not(inner) => format!("NOT {}", Self::render_expr(inner)),
+            Expr::IsNull(column) => format!("{} IS NULL", column),
+        }
+    }
+
+    fn render_op(op: CompareOp) -> &'static str {
+        match op {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Like => "LIKE",
+        }
+    }
+
+    fn render_join_type(join_type: JoinType) -> &'static str {
+        match join_type {
+            JoinType::Inner => "INNER",
+            JoinType::Left => "LEFT",
+            JoinType::Right => "RIGHT",
+            JoinType::Full => "FULL",
+        }
+    }
+
     pub fn parse(&self, query: &str) -> ParsedCommand {
         let raw = query.trim();
         if raw.is_empty() {
@@ -119,7 +660,9 @@ impl SQLParser {
             };
         }
 
-        let text = raw.trim_end_matches(';');
+        let trimmed = raw.trim_end_matches(';');
+        let text = Self::normalize_placeholders(trimmed);
+        let text = text.as_str();
         let tokens: Vec<&str> = text.split_whitespace().collect();
 
         if tokens.is_empty() {
@@ -133,10 +676,13 @@ impl SQLParser {
 
         let command = match command_str.as_str() {
             "COMMIT" => CommandType::Commit,
+            "BEGIN" => CommandType::Begin,
+            "ROLLBACK" => CommandType::Rollback,
             "CREATE" if tokens.len() > 1 && tokens[1].to_uppercase() == "DATABASE" => {
                 if tokens.len() > 2 {
                     CommandType::CreateDatabase {
                         name: tokens[2].to_lowercase(),
+                        engine: self.parse_engine_clause(&tokens),
                     }
                 } else {
                     CommandType::Unknown
@@ -190,6 +736,44 @@ impl SQLParser {
         }
     }
 
+    /// Rewrite bare `?` placeholders into sequential `$1`, `$2`, ... (the
+    /// same positional syntax `parse_literal` already recognizes), skipping
+    /// any `?` inside a quoted string literal.
+    fn normalize_placeholders(text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut in_string = false;
+        let mut next_index = 1;
+        let mut chars = text.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\'' => {
+                    in_string = !in_string;
+                    out.push(ch);
+                }
+                '?' if !in_string && !chars.peek().map_or(false, |c| c.is_ascii_digit()) => {
+                    out.push('$');
+                    out.push_str(&next_index.to_string());
+                    next_index += 1;
+                }
+                _ => out.push(ch),
+            }
+        }
+        out
+    }
+
+    /// Look for a trailing `ENGINE <btree|memory>` clause after `CREATE
+    /// DATABASE <name>`, defaulting to `btree` when absent or unrecognized.
+    fn parse_engine_clause(&self, tokens: &[&str]) -> String {
+        if tokens.len() > 4 && tokens[3].to_uppercase() == "ENGINE" {
+            match tokens[4].to_lowercase().as_str() {
+                "memory" => return "memory".to_string(),
+                "btree" => return "btree".to_string(),
+                _ => {}
+            }
+        }
+        "btree".to_string()
+    }
+
     fn parse_create_table(&self, text: &str) -> CommandType {
         if let Some(paren_start) = text.find('(') {
             if let Some(paren_end) = text.rfind(')') {
@@ -244,15 +828,81 @@ impl SQLParser {
         CommandType::Unknown
     }
 
+    /// Parse `INSERT INTO t [(col, ...)] VALUES (v1, v2, ...), (...), ...`,
+    /// accepting an optional target column list and one or more value
+    /// tuples. Every tuple must share the same arity, and — when given — the
+    /// column list's length must match it too; either mismatch is reported
+    /// as `Unknown` the same as any other malformed statement here.
     fn parse_insert(&self, text: &str) -> CommandType {
-        let re = Regex::new(r"(?i)INSERT\s+INTO\s+(\w+)\s+VALUES\s*\((.+)\)").unwrap();
-        if let Some(caps) = re.captures(text) {
-            let table = caps.get(1).unwrap().as_str().to_lowercase();
-            let values_str = caps.get(2).unwrap().as_str();
-            let values = self.parse_value_list(values_str);
-            return CommandType::Insert { table, values };
+        let re = Regex::new(r"(?i)^INSERT\s+INTO\s+(\w+)\s*(?:\(([^)]*)\)\s*)?VALUES\s*(.+)$").unwrap();
+        let caps = match re.captures(text.trim()) {
+            Some(caps) => caps,
+            None => return CommandType::Unknown,
+        };
+        let table = caps.get(1).unwrap().as_str().to_lowercase();
+        let columns = caps.get(2).map(|m| {
+            m.as_str()
+                .split(',')
+                .map(|c| c.trim().to_lowercase())
+                .collect::<Vec<String>>()
+        });
+        let tuples = self.split_value_tuples(caps.get(3).unwrap().as_str());
+        if tuples.is_empty() {
+            return CommandType::Unknown;
         }
-        CommandType::Unknown
+
+        let rows: Vec<Vec<Value>> = tuples.iter().map(|t| self.parse_value_list(t)).collect();
+        let arity = rows[0].len();
+        if rows.iter().any(|row| row.len() != arity) {
+            return CommandType::Unknown;
+        }
+        if let Some(cols) = &columns {
+            if cols.len() != arity {
+                return CommandType::Unknown;
+            }
+        }
+
+        CommandType::Insert { table, columns, rows }
+    }
+
+    /// Split a `VALUES` clause's `(...), (...), ...` tuples into their raw
+    /// inner text, respecting quoted strings so a `,` or `)` inside a text
+    /// literal doesn't end a tuple early.
+    fn split_value_tuples(&self, text: &str) -> Vec<String> {
+        let mut tuples = Vec::new();
+        let mut current = String::new();
+        let mut depth = 0u32;
+        let mut in_string = false;
+
+        for ch in text.chars() {
+            match ch {
+                '\'' => {
+                    in_string = !in_string;
+                    if depth > 0 {
+                        current.push(ch);
+                    }
+                }
+                '(' if !in_string => {
+                    depth += 1;
+                    if depth > 1 {
+                        current.push(ch);
+                    }
+                }
+                ')' if !in_string => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        tuples.push(current.clone());
+                        current.clear();
+                    } else {
+                        current.push(ch);
+                    }
+                }
+                _ if depth > 0 => current.push(ch),
+                _ => {}
+            }
+        }
+
+        tuples
     }
 
     fn parse_update(&self, text: &str) -> CommandType {
@@ -317,43 +967,177 @@ impl SQLParser {
     }
 
     fn parse_select(&self, text: &str) -> CommandType {
-        let re = Regex::new(
-            r"(?i)SELECT\s+(?P<cols>.+?)\s+FROM\s+(?P<table>\w+)(?:\s+INNER\s+JOIN\s+(?P<join_table>\w+)\s+ON\s+(?P<left_table>\w+)\.(?P<left_col>\w+)\s*=\s*(?P<right_table>\w+)\.(?P<right_col>\w+))?(?:\s+WHERE\s+(?P<where_col>\w+)\s*=\s*(?P<where_val>.+))?"
-        ).unwrap();
-
-        if let Some(caps) = re.captures(text) {
-            let cols_str = caps.name("cols").unwrap().as_str();
-            let columns: Vec<String> = cols_str.split(',').map(|s| s.trim().to_string()).collect();
-            let table = caps.name("table").unwrap().as_str().to_lowercase();
-
-            let condition = if let Some(where_col) = caps.name("where_col") {
-                let column = where_col.as_str().to_lowercase();
-                let value = self.parse_literal(caps.name("where_val").unwrap().as_str());
-                Some(Condition { column, value })
-            } else {
-                None
-            };
+        // Trailing clauses are stripped right-to-left (LIMIT, then ORDER BY,
+        // then GROUP BY, then WHERE) before the FROM/JOIN regex runs, since a
+        // JOIN's `ON` clause can itself be an arbitrary `AND`-chained boolean
+        // expression and so can't be bounded by a single greedy capture group
+        // the way a lone equality join could.
+        let mut remaining = text;
 
-            let join = if let Some(join_table) = caps.name("join_table") {
-                Some(JoinInfo {
-                    table: join_table.as_str().to_lowercase(),
-                    left_table: caps.name("left_table").unwrap().as_str().to_lowercase(),
-                    left_column: caps.name("left_col").unwrap().as_str().to_lowercase(),
-                    right_table: caps.name("right_table").unwrap().as_str().to_lowercase(),
-                    right_column: caps.name("right_col").unwrap().as_str().to_lowercase(),
-                })
-            } else {
-                None
-            };
+        let (limit, offset) = match Self::find_clause(remaining, " LIMIT ") {
+            Some(idx) => {
+                let clause = remaining[idx + 7..].trim().to_string();
+                remaining = &remaining[..idx];
+                self.parse_limit_offset(&clause)
+            }
+            None => (None, None),
+        };
 
-            return CommandType::Select {
-                table,
-                columns,
-                condition,
-                join,
+        let order_by = match Self::find_clause(remaining, " ORDER BY ") {
+            Some(idx) => {
+                let clause = remaining[idx + 10..].trim().to_string();
+                remaining = &remaining[..idx];
+                self.parse_order_by(&clause)
+            }
+            None => Vec::new(),
+        };
+
+        let group_by = match Self::find_clause(remaining, " GROUP BY ") {
+            Some(idx) => {
+                let clause = remaining[idx + 10..].trim().to_string();
+                remaining = &remaining[..idx];
+                clause.split(',').map(|c| c.trim().to_lowercase()).collect()
+            }
+            None => Vec::new(),
+        };
+
+        let where_clause = match Self::find_clause(remaining, " WHERE ") {
+            Some(idx) => {
+                let clause = remaining[idx + 7..].trim().to_string();
+                remaining = &remaining[..idx];
+                Some(clause)
+            }
+            None => None,
+        };
+        let condition = where_clause.and_then(|w| self.parse_condition(&w));
+
+        let head_re = Regex::new(r"(?i)^SELECT\s+(?P<cols>.+?)\s+FROM\s+(?P<table>\w+)\s*(?P<joins>.*)$").unwrap();
+        let caps = match head_re.captures(remaining.trim()) {
+            Some(caps) => caps,
+            None => return CommandType::Unknown,
+        };
+
+        let cols_str = caps.name("cols").unwrap().as_str();
+        let columns = self.parse_result_columns(cols_str);
+        let table = caps.name("table").unwrap().as_str().to_lowercase();
+        let joins_text = caps.name("joins").map(|m| m.as_str()).unwrap_or("");
+        let joins = match self.parse_joins(joins_text) {
+            Some(joins) => joins,
+            None => return CommandType::Unknown,
+        };
+
+        CommandType::Select {
+            table,
+            columns,
+            condition,
+            joins,
+            order_by,
+            limit,
+            offset,
+            group_by,
+        }
+    }
+
+    /// Parse a chain of `[INNER|LEFT [OUTER]|RIGHT [OUTER]|FULL [OUTER]] JOIN
+    /// <table> ON <condition>` clauses following a `SELECT`'s `FROM` table.
+    /// Each join's `ON` text runs from the end of its own `JOIN ... ON`
+    /// header to the start of the next join's header (or the end of the
+    /// text, for the last one), so its condition can be any boolean
+    /// expression the shared condition parser understands. Returns `None`
+    /// (an unparseable statement) if any join's `ON` condition is empty or
+    /// doesn't parse. Empty `text` parses as zero joins.
+    fn parse_joins(&self, text: &str) -> Option<Vec<JoinInfo>> {
+        let header_re = Regex::new(
+            r"(?i)\b(INNER|LEFT(?:\s+OUTER)?|RIGHT(?:\s+OUTER)?|FULL(?:\s+OUTER)?)?\s*JOIN\s+(\w+)\s+ON\s+",
+        )
+        .unwrap();
+
+        let headers: Vec<_> = header_re.captures_iter(text).collect();
+        if headers.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let mut joins = Vec::new();
+        for (i, caps) in headers.iter().enumerate() {
+            let whole = caps.get(0).unwrap();
+            let on_start = whole.end();
+            let on_end = headers.get(i + 1).map(|next| next.get(0).unwrap().start()).unwrap_or(text.len());
+            let on_text = text[on_start..on_end].trim();
+            let on = self.parse_condition(on_text)?;
+
+            let join_type = match caps.get(1).map(|m| m.as_str().to_uppercase()) {
+                Some(kw) if kw.starts_with("LEFT") => JoinType::Left,
+                Some(kw) if kw.starts_with("RIGHT") => JoinType::Right,
+                Some(kw) if kw.starts_with("FULL") => JoinType::Full,
+                _ => JoinType::Inner,
             };
+
+            joins.push(JoinInfo {
+                table: caps.get(2).unwrap().as_str().to_lowercase(),
+                join_type,
+                on,
+            });
         }
-        CommandType::Unknown
+
+        Some(joins)
+    }
+
+    /// Find a trailing ` KEYWORD ` clause's start index, case-insensitively.
+    fn find_clause(text: &str, keyword: &str) -> Option<usize> {
+        text.to_uppercase().find(keyword)
+    }
+
+    /// Parse a `SELECT`'s result column list, recognizing `FUNC(arg)` as an
+    /// aggregate and everything else as a plain (possibly `table.column` or
+    /// `*`) column.
+    fn parse_result_columns(&self, cols_str: &str) -> Vec<ResultColumn> {
+        cols_str
+            .split(',')
+            .map(|raw| {
+                let chunk = raw.trim();
+                if chunk.ends_with(')') {
+                    if let Some(paren_start) = chunk.find('(') {
+                        let func_name = chunk[..paren_start].trim().to_uppercase();
+                        if let Some(func) = AggregateFunc::from_name(&func_name) {
+                            let arg = chunk[paren_start + 1..chunk.len() - 1].trim().to_string();
+                            return ResultColumn::Aggregate { func, arg };
+                        }
+                    }
+                }
+                ResultColumn::Plain(chunk.to_string())
+            })
+            .collect()
+    }
+
+    /// Parse a `LIMIT` clause's body, e.g. `10` or `10 OFFSET 5`.
+    fn parse_limit_offset(&self, clause: &str) -> (Option<u64>, Option<u64>) {
+        let upper = clause.to_uppercase();
+        if let Some(idx) = upper.find(" OFFSET ") {
+            let limit = clause[..idx].trim().parse::<u64>().ok();
+            let offset = clause[idx + 8..].trim().parse::<u64>().ok();
+            (limit, offset)
+        } else {
+            (clause.trim().parse::<u64>().ok(), None)
+        }
+    }
+
+    /// Parse an `ORDER BY` clause's body into its sort keys, in order.
+    fn parse_order_by(&self, clause: &str) -> Vec<OrderKey> {
+        clause
+            .split(',')
+            .filter_map(|chunk| {
+                let parts: Vec<&str> = chunk.trim().split_whitespace().collect();
+                if parts.is_empty() {
+                    return None;
+                }
+                let column = parts[0].to_lowercase();
+                let descending = parts
+                    .get(1)
+                    .map(|p| p.eq_ignore_ascii_case("DESC"))
+                    .unwrap_or(false);
+                Some(OrderKey { column, descending })
+            })
+            .collect()
     }
 
     fn parse_value_list(&self, segment: &str) -> Vec<Value> {
@@ -395,18 +1179,163 @@ impl SQLParser {
         values
     }
 
-    fn parse_condition(&self, clause: &str) -> Option<Condition> {
-        if let Some(eq_idx) = clause.find('=') {
-            let column = clause[..eq_idx].trim().to_lowercase();
-            let value = self.parse_literal(clause[eq_idx + 1..].trim());
-            return Some(Condition { column, value });
+    /// Parse a `WHERE` clause into an `Expr` tree: tokenize into identifiers,
+    /// literals, operators, and parentheses, then a recursive-descent parser
+    /// takes over with `OR` at the lowest precedence, then `AND`, then `NOT`,
+    /// then primary comparisons (`=`, `!=`/`<>`, `<`, `<=`, `>`, `>=`,
+    /// `LIKE`, `IS [NOT] NULL`, `BETWEEN ... AND ...`) and parenthesized
+    /// groups.
+    fn parse_condition(&self, clause: &str) -> Option<Expr> {
+        let tokens = self.tokenize_condition(clause.trim());
+        if tokens.is_empty() {
+            return None;
+        }
+        let mut parser = CondParser { tokens: &tokens, pos: 0 };
+        parser.parse_or()
+    }
+
+    /// Split a `WHERE` clause into condition tokens, treating quoted strings
+    /// as opaque the same way `parse_value_list` does.
+    fn tokenize_condition(&self, clause: &str) -> Vec<Token> {
+        let chars: Vec<char> = clause.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            if c.is_whitespace() {
+                i += 1;
+                continue;
+            }
+            match c {
+                '(' => {
+                    tokens.push(Token::LParen);
+                    i += 1;
+                }
+                ')' => {
+                    tokens.push(Token::RParen);
+                    i += 1;
+                }
+                '\'' | '"' => {
+                    let quote = c;
+                    let mut text = String::new();
+                    i += 1;
+                    while i < chars.len() && chars[i] != quote {
+                        text.push(chars[i]);
+                        i += 1;
+                    }
+                    i += 1; // skip closing quote
+                    tokens.push(Token::Literal(Value::Text(text)));
+                }
+                '!' if chars.get(i + 1) == Some(&'=') => {
+                    tokens.push(Token::Op("!=".to_string()));
+                    i += 2;
+                }
+                '<' if chars.get(i + 1) == Some(&'=') => {
+                    tokens.push(Token::Op("<=".to_string()));
+                    i += 2;
+                }
+                '<' if chars.get(i + 1) == Some(&'>') => {
+                    tokens.push(Token::Op("!=".to_string()));
+                    i += 2;
+                }
+                '>' if chars.get(i + 1) == Some(&'=') => {
+                    tokens.push(Token::Op(">=".to_string()));
+                    i += 2;
+                }
+                '<' => {
+                    tokens.push(Token::Op("<".to_string()));
+                    i += 1;
+                }
+                '>' => {
+                    tokens.push(Token::Op(">".to_string()));
+                    i += 1;
+                }
+                '=' => {
+                    tokens.push(Token::Op("=".to_string()));
+                    i += 1;
+                }
+                _ => {
+                    let start = i;
+                    while i < chars.len() && !chars[i].is_whitespace() && !"()'\"<>=!".contains(chars[i]) {
+                        i += 1;
+                    }
+                    let word: String = chars[start..i].iter().collect();
+                    if word.is_empty() {
+                        i += 1;
+                        continue;
+                    }
+                    tokens.push(self.classify_word(&word));
+                }
+            }
+        }
+
+        tokens
+    }
+
+    /// Decide whether a bare word is a keyword, a literal, or an identifier.
+    fn classify_word(&self, word: &str) -> Token {
+        match word.to_uppercase().as_str() {
+            "AND" => return Token::And,
+            "OR" => return Token::Or,
+            "NOT" => return Token::Not,
+            "IS" => return Token::Is,
+            "NULL" => return Token::Null,
+            "LIKE" => return Token::Like,
+            "BETWEEN" => return Token::Between,
+            _ => {}
+        }
+        let starts_literal = word
+            .chars()
+            .next()
+            .map(|c| c.is_ascii_digit() || c == '-' || c == '$' || c == '?' || c == ':')
+            .unwrap_or(false);
+        if starts_literal {
+            Token::Literal(self.parse_literal(word))
+        } else {
+            Token::Ident(word.to_lowercase())
         }
-        None
+    }
+
+    /// Public entry point for parsing a single literal outside of a full
+    /// statement, e.g. a bound parameter's text-format wire value.
+    pub fn parse_value(&self, text: &str) -> Value {
+        self.parse_literal(text)
     }
 
     fn parse_literal(&self, text: &str) -> Value {
         let trimmed = text.trim();
 
+        // A bound-parameter placeholder, e.g. `$1`, substituted by
+        // `SQLExecutor::bind`. `$name` (no digits) is a named `Value::Param`
+        // instead, substituted by `ParsedCommand::bind_named`.
+        if let Some(rest) = trimmed.strip_prefix('$') {
+            if let Ok(n) = rest.parse::<usize>() {
+                if n >= 1 {
+                    return Value::Placeholder(n - 1);
+                }
+            } else if Self::is_param_name(rest) {
+                return Value::Param(ParamRef::Named(rest.to_string()));
+            }
+        }
+
+        // A positional `Value::Param`, e.g. `?1`, substituted by
+        // `ParsedCommand::bind`.
+        if let Some(rest) = trimmed.strip_prefix('?') {
+            if let Ok(n) = rest.parse::<usize>() {
+                if n >= 1 {
+                    return Value::Param(ParamRef::Positional(n - 1));
+                }
+            }
+        }
+
+        // A named `Value::Param`, e.g. `:id`.
+        if let Some(rest) = trimmed.strip_prefix(':') {
+            if Self::is_param_name(rest) {
+                return Value::Param(ParamRef::Named(rest.to_string()));
+            }
+        }
+
         // Remove quotes if present
         if (trimmed.starts_with('\'') && trimmed.ends_with('\''))
             || (trimmed.starts_with('"') && trimmed.ends_with('"'))
@@ -427,6 +1356,160 @@ impl SQLParser {
         // Default to text
         Value::Text(trimmed.to_string())
     }
+
+    /// Whether `text` is a valid named-parameter name: non-empty and made
+    /// only of identifier characters, so `$1.50` or a bare `:`/`$` doesn't
+    /// get misread as a named param.
+    fn is_param_name(text: &str) -> bool {
+        !text.is_empty() && text.chars().all(|c| c.is_alphanumeric() || c == '_')
+    }
+}
+
+/// A single lexical unit of a tokenized `WHERE` clause.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(Value),
+    And,
+    Or,
+    Not,
+    Is,
+    Null,
+    Like,
+    Between,
+    Op(String),
+    LParen,
+    RParen,
+}
+
+/// Recursive-descent parser over a `WHERE` clause's tokens. Precedence, low
+/// to high: `OR`, `AND`, `NOT`, then primary comparisons/parenthesized
+/// groups, matching the way most SQL dialects read a boolean expression.
+struct CondParser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> CondParser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Option<Expr> {
+        let mut left = self.parse_and()?;
+        while self.eat(&Token::Or) {
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Some(left)
+    }
+
+    fn parse_and(&mut self) -> Option<Expr> {
+        let mut left = self.parse_not()?;
+        while self.eat(&Token::And) {
+            let right = self.parse_not()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Some(left)
+    }
+
+    fn parse_not(&mut self) -> Option<Expr> {
+        if self.eat(&Token::Not) {
+            let inner = self.parse_not()?;
+            return Some(Expr::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Option<Expr> {
+        if self.eat(&Token::LParen) {
+            let expr = self.parse_or()?;
+            self.eat(&Token::RParen);
+            return Some(expr);
+        }
+
+        let column = match self.next()? {
+            Token::Ident(name) => name,
+            _ => return None,
+        };
+
+        match self.peek()? {
+            Token::Is => {
+                self.next();
+                let negate = self.eat(&Token::Not);
+                self.eat(&Token::Null);
+                let expr = Expr::IsNull(column);
+                Some(if negate { Expr::Not(Box::new(expr)) } else { expr })
+            }
+            Token::Between => {
+                self.next();
+                let low = self.parse_literal_token()?;
+                self.eat(&Token::And);
+                let high = self.parse_literal_token()?;
+                Some(Expr::And(
+                    Box::new(Expr::Compare {
+                        column: column.clone(),
+                        op: CompareOp::Ge,
+                        value: low,
+                    }),
+                    Box::new(Expr::Compare {
+                        column,
+                        op: CompareOp::Le,
+                        value: high,
+                    }),
+                ))
+            }
+            Token::Like => {
+                self.next();
+                let value = self.parse_literal_token()?;
+                Some(Expr::Compare { column, op: CompareOp::Like, value })
+            }
+            Token::Op(_) => {
+                let op = match self.next()? {
+                    Token::Op(op) => op,
+                    _ => unreachable!(),
+                };
+                let value = self.parse_literal_token()?;
+                let compare_op = match op.as_str() {
+                    "=" => CompareOp::Eq,
+                    "!=" => CompareOp::Ne,
+                    "<" => CompareOp::Lt,
+                    "<=" => CompareOp::Le,
+                    ">" => CompareOp::Gt,
+                    ">=" => CompareOp::Ge,
+                    _ => return None,
+                };
+                Some(Expr::Compare { column, op: compare_op, value })
+            }
+            _ => None,
+        }
+    }
+
+    /// The value on the right of a comparison: either a literal, or — so a
+    /// join's `ON a.x = b.x` can be parsed the same way a `WHERE` is — a bare
+    /// identifier, taken as a reference to another column.
+    fn parse_literal_token(&mut self) -> Option<Value> {
+        match self.next()? {
+            Token::Literal(value) => Some(value),
+            Token::Ident(name) => Some(Value::Column(name)),
+            _ => None,
+        }
+    }
 }
 
 impl Default for SQLParser {
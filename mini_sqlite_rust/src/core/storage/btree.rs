@@ -1,468 +1,180 @@
-/// Simplified in-memory B-Tree style table manager.
-use crate::core::parser::{Condition, JoinInfo, Value};
+/// Disk-backed `StorageEngine`: table/row state lives in a `TableStore` and
+/// is persisted to a `.dat` file through the `Pager` after every mutation.
+use crate::core::parser::{Expr, JoinInfo, Value};
+use crate::core::storage::compression;
 use crate::core::storage::pager::Pager;
-use serde::{Deserialize, Serialize};
+use crate::core::storage::storage_engine::StorageEngine;
+use crate::core::storage::table_store::{Row, TableSnapshot, TableStore};
 use serde_json::{from_slice, to_vec};
 use std::collections::HashMap;
 
-type Row = HashMap<String, Value>;
-type Index = HashMap<String, Vec<usize>>;
-
-#[derive(Clone)]
-pub struct TableMeta {
-    pub columns: Vec<String>,
-    pub rows: Vec<Row>,
-    pub indexes: HashMap<String, Index>,
-}
-
-#[derive(Serialize, Deserialize)]
-struct TableSnapshot {
-    columns: Vec<String>,
-    rows: Vec<Row>,
-    indexes: Vec<String>,
-}
+/// Compress the persisted snapshot once its serialized size passes this many
+/// bytes; small databases stay uncompressed to avoid paying the codec's
+/// overhead for no benefit.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 4096;
 
 pub struct BTreeStorage {
     pager: Option<Pager>,
-    tables: HashMap<String, TableMeta>,
+    store: TableStore,
+    /// While `true`, mutating methods skip `persist()`; `commit_txn` flushes
+    /// once for the whole transaction instead of once per statement.
+    in_txn: bool,
+    /// `None` disables compression; `Some(n)` compresses snapshots over `n`
+    /// serialized bytes.
+    compression_threshold: Option<usize>,
 }
 
 impl BTreeStorage {
+    /// Compresses snapshots over `DEFAULT_COMPRESSION_THRESHOLD` bytes; use
+    /// `with_compression` to pick a different threshold or turn it off.
     pub fn new(pager: Option<Pager>) -> Self {
+        Self::with_compression(pager, Some(DEFAULT_COMPRESSION_THRESHOLD))
+    }
+
+    /// Like `new`, but with an explicit compression threshold. `None` turns
+    /// compression off entirely.
+    pub fn with_compression(pager: Option<Pager>, compression_threshold: Option<usize>) -> Self {
         let mut storage = BTreeStorage {
             pager,
-            tables: HashMap::new(),
+            store: TableStore::new(),
+            in_txn: false,
+            compression_threshold,
         };
         storage.load();
         storage
     }
 
-    pub fn create_table(&mut self, name: &str, columns: Vec<String>) {
-        let meta = TableMeta {
-            columns,
-            rows: Vec::new(),
-            indexes: HashMap::new(),
+    fn load(&mut self) {
+        let data = match self.pager.as_ref().map(|pager| pager.read_blob()) {
+            Some(bytes) if !bytes.is_empty() => bytes,
+            _ => return,
         };
-        self.tables.insert(name.to_string(), meta);
-        self.persist();
+        let data = compression::decode(&data);
+        if let Ok(snapshot) = from_slice::<HashMap<String, TableSnapshot>>(&data) {
+            self.store.load_snapshot(snapshot);
+        }
     }
 
-    pub fn drop_table(&mut self, name: &str) {
-        self.tables.remove(name);
-        self.persist();
+    fn persist(&mut self) {
+        if self.in_txn {
+            return;
+        }
+        if let Some(ref mut pager) = self.pager {
+            if let Ok(bytes) = to_vec(&self.store.to_snapshot()) {
+                let blob = compression::encode(&bytes, self.compression_threshold);
+                // `write_blob` remaps (and, in mmap-backed mode, truncates)
+                // down to exactly what the new snapshot needs, so a
+                // `DropTable` or bulk delete's smaller snapshot actually
+                // shrinks the `.dat` file instead of leaving it at its
+                // largest-ever size.
+                pager.write_blob(&blob);
+            }
+        }
     }
+}
 
-    pub fn table_exists(&self, name: &str) -> bool {
-        self.tables.contains_key(name)
+impl StorageEngine for BTreeStorage {
+    fn table_exists(&self, name: &str) -> bool {
+        self.store.table_exists(name)
     }
 
-    pub fn add_column(&mut self, name: &str, column: String) {
-        let indexes_to_rebuild = {
-            let table = match self.tables.get_mut(name) {
-                Some(table) => table,
-                None => return,
-            };
-            if table.columns.contains(&column) {
-                return;
-            }
-            table.columns.push(column.clone());
-            for row in &mut table.rows {
-                row.insert(column.clone(), Value::Null);
-            }
-            table.indexes.keys().cloned().collect::<Vec<String>>()
-        };
+    fn columns(&self, name: &str) -> Option<Vec<String>> {
+        self.store.columns(name)
+    }
 
-        for col in indexes_to_rebuild {
-            self.rebuild_index(name, &col);
-        }
+    fn create_table(&mut self, name: &str, columns: Vec<String>) {
+        self.store.create_table(name, columns);
         self.persist();
     }
 
-    pub fn create_index(&mut self, table_name: &str, column: &str) {
-        if let Some(table) = self.tables.get_mut(table_name) {
-            if !table.indexes.contains_key(column) {
-                self.rebuild_index(table_name, column);
-            }
-        }
+    fn drop_table(&mut self, name: &str) {
+        self.store.drop_table(name);
         self.persist();
     }
 
-    pub fn drop_index(&mut self, table_name: &str, column: &str) {
-        if let Some(table) = self.tables.get_mut(table_name) {
-            table.indexes.remove(column);
-            self.persist();
-        }
+    fn add_column(&mut self, name: &str, column: String) {
+        self.store.add_column(name, column);
+        self.persist();
     }
 
-    pub fn insert_row(&mut self, table_name: &str, values: Vec<Value>) -> Result<Row, String> {
-        let table = self
-            .tables
-            .get_mut(table_name)
-            .ok_or_else(|| format!("Table '{}' not found", table_name))?;
-
-        if values.len() != table.columns.len() {
-            return Err("Value count does not match table schema".to_string());
-        }
-
-        let mut row = Row::new();
-        for (col, val) in table.columns.iter().zip(values.iter()) {
-            row.insert(col.clone(), val.clone());
-        }
-
-        let row_idx = table.rows.len();
-        table.rows.push(row.clone());
-
-        // Update indexes
-        for (column, index) in &mut table.indexes {
-            if let Some(value) = row.get(column) {
-                let key = format!("{:?}", value);
-                index.entry(key).or_insert_with(Vec::new).push(row_idx);
-            }
-        }
+    fn create_index(&mut self, table_name: &str, column: &str) {
+        self.store.create_index(table_name, column);
+        self.persist();
+    }
 
+    fn drop_index(&mut self, table_name: &str, column: &str) {
+        self.store.drop_index(table_name, column);
         self.persist();
+    }
 
+    fn insert_row(&mut self, table_name: &str, values: Vec<Value>) -> Result<Row, String> {
+        let row = self.store.insert_row(table_name, values)?;
+        self.persist();
         Ok(row)
     }
 
-    pub fn select_rows(
-        &self,
-        table_name: &str,
-        columns: &[String],
-        condition: Option<&Condition>,
-        join: Option<&JoinInfo>,
-    ) -> Result<Vec<Row>, String> {
-        if let Some(join_info) = join {
-            return self.join_rows(table_name, columns, condition, join_info);
-        }
-
-        if !self.tables.contains_key(table_name) {
-            return Err(format!("Table '{}' not found", table_name));
-        }
-
-        let rows: Vec<&Row> = self.rows_for(table_name, condition).collect();
-
-        if columns.len() == 1 && columns[0] == "*" {
-            return Ok(rows.iter().map(|r| (*r).clone()).collect());
-        }
-
-        let mut selected = Vec::new();
-        for row in rows {
-            let mut projected = Row::new();
-            for col in columns {
-                let lookup = col.split('.').last().unwrap_or(col);
-                projected.insert(col.clone(), row.get(lookup).cloned().unwrap_or(Value::Null));
-            }
-            selected.push(projected);
-        }
-
-        Ok(selected)
+    fn insert_rows(&mut self, table_name: &str, values_list: Vec<Vec<Value>>) -> Result<Vec<Row>, String> {
+        let rows = self.store.insert_rows(table_name, values_list)?;
+        self.persist();
+        Ok(rows)
     }
 
-    pub fn update_rows(
+    fn update_rows(
         &mut self,
         table_name: &str,
         assignments: &HashMap<String, Value>,
-        condition: Option<&Condition>,
+        condition: Option<&Expr>,
     ) -> Result<usize, String> {
-        if !self.tables.contains_key(table_name) {
-            return Err(format!("Table '{}' not found", table_name));
-        }
-
-        let indexes_to_rebuild = self
-            .tables
-            .get(table_name)
-            .unwrap()
-            .indexes
-            .keys()
-            .cloned()
-            .collect::<Vec<String>>();
-
-        let indices = {
-            let table = self.tables.get(table_name).unwrap();
-            if let Some(cond) = condition {
-                table
-                    .rows
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(idx, row)| match row.get(&cond.column) {
-                        Some(value) if Self::values_equal(value, &cond.value) => Some(idx),
-                        _ => None,
-                    })
-                    .collect::<Vec<usize>>()
-            } else {
-                (0..table.rows.len()).collect::<Vec<usize>>()
-            }
-        };
-
-        {
-            let table = self.tables.get_mut(table_name).unwrap();
-            for idx in &indices {
-                if let Some(row) = table.rows.get_mut(*idx) {
-                    for (key, value) in assignments {
-                        row.insert(key.clone(), value.clone());
-                    }
-                }
-            }
-        }
-
-        for column in indexes_to_rebuild {
-            self.rebuild_index(table_name, &column);
-        }
-
-        if !indices.is_empty() {
+        let count = self.store.update_rows(table_name, assignments, condition)?;
+        if count > 0 {
             self.persist();
         }
-
-        Ok(indices.len())
+        Ok(count)
     }
 
-    pub fn delete_rows(
-        &mut self,
-        table_name: &str,
-        condition: Option<&Condition>,
-    ) -> Result<usize, String> {
-        let (kept, deleted, index_columns) = {
-            let table = self
-                .tables
-                .get(table_name)
-                .ok_or_else(|| format!("Table '{}' not found", table_name))?;
-            let mut kept_rows = Vec::new();
-            let mut deleted_count = 0;
-            if let Some(cond) = condition {
-                for row in &table.rows {
-                    match row.get(&cond.column) {
-                        Some(value) if Self::values_equal(value, &cond.value) => deleted_count += 1,
-                        _ => kept_rows.push(row.clone()),
-                    }
-                }
-            } else {
-                deleted_count = table.rows.len();
-            }
-            let columns = table.indexes.keys().cloned().collect::<Vec<String>>();
-            (kept_rows, deleted_count, columns)
-        };
-
-        let table = self.tables.get_mut(table_name).unwrap();
-        table.rows = kept;
-
-        for column in index_columns {
-            self.rebuild_index(table_name, &column);
-        }
-
-        if deleted > 0 {
+    fn delete_rows(&mut self, table_name: &str, condition: Option<&Expr>) -> Result<usize, String> {
+        let count = self.store.delete_rows(table_name, condition)?;
+        if count > 0 {
             self.persist();
         }
-
-        Ok(deleted)
+        Ok(count)
     }
 
-    fn rows_for<'a>(
-        &'a self,
-        table_name: &str,
-        condition: Option<&'a Condition>,
-    ) -> Box<dyn Iterator<Item = &'a Row> + 'a> {
-        let table = match self.tables.get(table_name) {
-            Some(t) => t,
-            None => return Box::new(std::iter::empty()),
-        };
-
-        if let Some(cond) = condition {
-            let column = cond.column.clone();
-            let value = cond.value.clone();
-
-            if let Some(index) = table.indexes.get(&column) {
-                let key = format!("{:?}", value);
-                if let Some(indices) = index.get(&key) {
-                    let rows: Vec<&Row> = indices
-                        .iter()
-                        .filter_map(|idx| table.rows.get(*idx))
-                        .collect();
-                    return Box::new(rows.into_iter());
-                }
-                return Box::new(std::iter::empty());
-            }
-
-            Box::new(table.rows.iter().filter(move |row| {
-                if let Some(row_value) = row.get(&column) {
-                    Self::values_equal(row_value, &value)
-                } else {
-                    false
-                }
-            }))
-        } else {
-            Box::new(table.rows.iter())
+    fn delete_rows_by_ids(&mut self, table_name: &str, ids: &[usize]) -> Result<usize, String> {
+        let count = self.store.delete_rows_by_ids(table_name, ids)?;
+        if count > 0 {
+            self.persist();
         }
+        Ok(count)
     }
 
-    fn join_rows(
+    fn select_rows(
         &self,
-        left_table_name: &str,
+        table_name: &str,
         columns: &[String],
-        condition: Option<&Condition>,
-        join: &JoinInfo,
+        condition: Option<&Expr>,
+        joins: &[JoinInfo],
     ) -> Result<Vec<Row>, String> {
-        let left_table = self
-            .tables
-            .get(left_table_name)
-            .ok_or_else(|| format!("Table '{}' not found", left_table_name))?;
-        let right_table = self
-            .tables
-            .get(&join.table)
-            .ok_or_else(|| format!("Table '{}' not found", join.table))?;
-
-        // Ensure index exists on right table
-        if !right_table.indexes.contains_key(&join.right_column) {
-            // We need mutable access, so we'll do this differently
-            // For now, we'll scan without index
-        }
-
-        let left_rows: Vec<&Row> = self.rows_for(left_table_name, condition).collect();
-        let mut result = Vec::new();
-
-        for left_row in left_rows {
-            if let Some(key_value) = left_row.get(&join.left_column) {
-                for right_row in &right_table.rows {
-                    if let Some(right_value) = right_row.get(&join.right_column) {
-                        if Self::values_equal(key_value, right_value) {
-                            let mut combined = Row::new();
-
-                            // Add left table columns with prefix
-                            for col in &left_table.columns {
-                                let key = format!("{}.{}", join.left_table, col);
-                                combined
-                                    .insert(key, left_row.get(col).cloned().unwrap_or(Value::Null));
-                            }
-
-                            // Add right table columns with prefix
-                            for col in &right_table.columns {
-                                let key = format!("{}.{}", join.right_table, col);
-                                combined.insert(
-                                    key,
-                                    right_row.get(col).cloned().unwrap_or(Value::Null),
-                                );
-                            }
-
-                            // Project requested columns if not *
-                            if columns.len() == 1 && columns[0] == "*" {
-                                result.push(combined);
-                            } else {
-                                let mut projected = Row::new();
-                                for col in columns {
-                                    if col.contains('.') {
-                                        projected.insert(
-                                            col.clone(),
-                                            combined.get(col).cloned().unwrap_or(Value::Null),
-                                        );
-                                    } else if left_table.columns.contains(col) {
-                                        projected.insert(
-                                            col.clone(),
-                                            left_row.get(col).cloned().unwrap_or(Value::Null),
-                                        );
-                                    } else if right_table.columns.contains(col) {
-                                        projected.insert(
-                                            col.clone(),
-                                            right_row.get(col).cloned().unwrap_or(Value::Null),
-                                        );
-                                    } else {
-                                        projected.insert(col.clone(), Value::Null);
-                                    }
-                                }
-                                result.push(projected);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(result)
+        self.store.select_rows(table_name, columns, condition, joins)
     }
 
-    fn rebuild_index(&mut self, table_name: &str, column: &str) {
-        if let Some(table) = self.tables.get_mut(table_name) {
-            let mut index = Index::new();
-
-            for (idx, row) in table.rows.iter().enumerate() {
-                if let Some(value) = row.get(column) {
-                    let key = format!("{:?}", value);
-                    index.entry(key).or_insert_with(Vec::new).push(idx);
-                }
-            }
-
-            table.indexes.insert(column.to_string(), index);
-        }
+    fn describe(&self) -> HashMap<String, HashMap<String, serde_json::Value>> {
+        self.store.describe()
     }
 
-    fn values_equal(a: &Value, b: &Value) -> bool {
-        match (a, b) {
-            (Value::Integer(x), Value::Integer(y)) => x == y,
-            (Value::Float(x), Value::Float(y)) => (x - y).abs() < f64::EPSILON,
-            (Value::Text(x), Value::Text(y)) => x == y,
-            (Value::Null, Value::Null) => true,
-            _ => false,
-        }
-    }
-
-    pub fn describe(&self) -> HashMap<String, HashMap<String, serde_json::Value>> {
-        let mut summary = HashMap::new();
-
-        for (name, table) in &self.tables {
-            let mut table_info = HashMap::new();
-            table_info.insert(
-                "columns".to_string(),
-                serde_json::to_value(&table.columns).unwrap(),
-            );
-            table_info.insert(
-                "row_count".to_string(),
-                serde_json::to_value(table.rows.len()).unwrap(),
-            );
-            let index_keys: Vec<String> = table.indexes.keys().cloned().collect();
-            table_info.insert(
-                "indexes".to_string(),
-                serde_json::to_value(index_keys).unwrap(),
-            );
-            summary.insert(name.clone(), table_info);
-        }
-
-        summary
+    fn begin_txn(&mut self) {
+        self.in_txn = true;
+        self.store.begin_txn();
     }
 
-    fn load(&mut self) {
-        let data = match self.pager.as_ref().map(|pager| pager.read_blob()) {
-            Some(bytes) if !bytes.is_empty() => bytes,
-            _ => return,
-        };
-        if let Ok(snapshot) = from_slice::<HashMap<String, TableSnapshot>>(&data) {
-            for (name, table) in snapshot {
-                let meta = TableMeta {
-                    columns: table.columns.clone(),
-                    rows: table.rows.clone(),
-                    indexes: HashMap::new(),
-                };
-                self.tables.insert(name.clone(), meta);
-                for column in table.indexes {
-                    self.rebuild_index(&name, &column);
-                }
-            }
-        }
+    fn commit_txn(&mut self) {
+        self.in_txn = false;
+        self.store.commit_txn();
+        self.persist();
     }
 
-    fn persist(&mut self) {
-        if let Some(ref mut pager) = self.pager {
-            let mut snapshot = HashMap::new();
-            for (name, table) in &self.tables {
-                let indexes: Vec<String> = table.indexes.keys().cloned().collect();
-                let table_snapshot = TableSnapshot {
-                    columns: table.columns.clone(),
-                    rows: table.rows.clone(),
-                    indexes,
-                };
-                snapshot.insert(name.clone(), table_snapshot);
-            }
-            if let Ok(bytes) = to_vec(&snapshot) {
-                pager.write_blob(&bytes);
-            }
-        }
+    fn rollback_txn(&mut self) {
+        self.in_txn = false;
+        self.store.rollback_txn();
     }
 }
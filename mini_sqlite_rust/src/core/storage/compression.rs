@@ -0,0 +1,57 @@
+/// Transparent compression for the snapshot blob `BTreeStorage` hands to
+/// `Pager::write_blob`. No compression crate is available, so this is a
+/// small run-length codec: simple, dependency-free, and still effective on
+/// JSON snapshots, which are full of repeated structural bytes and padding.
+/// Every encoded blob starts with a one-byte tag so `decode` knows whether
+/// the payload behind it is raw or run-length encoded.
+const TAG_PLAIN: u8 = 0;
+const TAG_RLE: u8 = 1;
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let byte = data[i];
+        let mut run = 1usize;
+        while i + run < data.len() && data[i + run] == byte && run < 255 {
+            run += 1;
+        }
+        out.push(run as u8);
+        out.push(byte);
+        i += run;
+    }
+    out
+}
+
+fn decompress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut pairs = data.chunks_exact(2);
+    for pair in &mut pairs {
+        out.extend(std::iter::repeat(pair[1]).take(pair[0] as usize));
+    }
+    out
+}
+
+/// Tag-prefix `data`, compressing it first when it exceeds `threshold`
+/// bytes. `threshold` of `None` disables compression entirely.
+pub fn encode(data: &[u8], threshold: Option<usize>) -> Vec<u8> {
+    let compress_it = matches!(threshold, Some(t) if data.len() > t);
+    let mut out = Vec::with_capacity(data.len() + 1);
+    if compress_it {
+        out.push(TAG_RLE);
+        out.extend(compress(data));
+    } else {
+        out.push(TAG_PLAIN);
+        out.extend_from_slice(data);
+    }
+    out
+}
+
+/// Strip the tag byte written by `encode` and decompress if needed.
+pub fn decode(data: &[u8]) -> Vec<u8> {
+    match data.split_first() {
+        Some((&TAG_RLE, rest)) => decompress(rest),
+        Some((_, rest)) => rest.to_vec(),
+        None => Vec::new(),
+    }
+}
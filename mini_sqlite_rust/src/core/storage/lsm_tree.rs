@@ -1,6 +1,14 @@
-/// In-memory placeholder for an LSM-style commit log.
+/// LSM-style commit log: recent mutation events sit in an in-memory
+/// memtable; once the memtable grows past a threshold it is frozen and
+/// flushed to an immutable on-disk `SSTable`, and overlapping SSTables are
+/// periodically compacted into the next level so the set of live segments
+/// stays small. A manifest file records which segments are live so they are
+/// recovered on restart instead of being lost.
+use crate::core::storage::sstable::SSTable;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
@@ -10,45 +18,270 @@ pub struct LogEntry {
     pub details: HashMap<String, serde_json::Value>,
 }
 
+/// Flush the memtable to a new SSTable once it holds more than this many entries.
+const MEMTABLE_LIMIT: usize = 10;
+/// Size-tiered compaction, modeled on parity-db's geometric tiering: level N
+/// holds up to `TIER_BASE * TIER_FACTOR^N` entries across its segments
+/// before they are merged up into level N+1.
+const TIER_BASE: usize = 2 * MEMTABLE_LIMIT;
+const TIER_FACTOR: usize = 4;
+
+fn tier_capacity(level: u32) -> usize {
+    TIER_BASE * TIER_FACTOR.pow(level)
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Manifest {
+    segments: Vec<ManifestSegment>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct ManifestSegment {
+    file: String,
+    level: u32,
+}
+
 pub struct LSMTreeStorage {
-    segments: Vec<LogEntry>,
+    memtable: Vec<LogEntry>,
+    sstables: Vec<SSTable>,
+    dir: Option<PathBuf>,
+    next_segment_id: usize,
 }
 
 impl LSMTreeStorage {
+    /// In-memory-only log with no on-disk segments; used where no data
+    /// directory is available.
     pub fn new() -> Self {
         LSMTreeStorage {
-            segments: Vec::new(),
+            memtable: Vec::new(),
+            sstables: Vec::new(),
+            dir: None,
+            next_segment_id: 0,
         }
     }
 
-    /// Record a mutation event.
+    /// Open (creating if needed) the segment directory and recover the set
+    /// of live SSTables from its manifest.
+    pub fn open<P: Into<PathBuf>>(dir: P) -> Self {
+        let dir = dir.into();
+        let _ = fs::create_dir_all(&dir);
+        let mut storage = LSMTreeStorage {
+            memtable: Vec::new(),
+            sstables: Vec::new(),
+            dir: Some(dir),
+            next_segment_id: 0,
+        };
+        storage.recover();
+        storage
+    }
+
+    fn manifest_path(&self) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join("MANIFEST.json"))
+    }
+
+    fn recover(&mut self) {
+        let path = match self.manifest_path() {
+            Some(path) => path,
+            None => return,
+        };
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(_) => return,
+        };
+        let manifest: Manifest = match serde_json::from_slice(&bytes) {
+            Ok(manifest) => manifest,
+            Err(_) => return,
+        };
+        let dir = self.dir.clone().unwrap();
+        for segment in manifest.segments {
+            if let Some(id) = segment_id(&segment.file) {
+                self.next_segment_id = self.next_segment_id.max(id + 1);
+            }
+            if let Some(sstable) = SSTable::open(dir.join(&segment.file), segment.level) {
+                self.sstables.push(sstable);
+            }
+        }
+    }
+
+    fn write_manifest(&self) {
+        let path = match self.manifest_path() {
+            Some(path) => path,
+            None => return,
+        };
+        let manifest = Manifest {
+            segments: self
+                .sstables
+                .iter()
+                .map(|sstable| ManifestSegment {
+                    file: sstable.file_name(),
+                    level: sstable.level,
+                })
+                .collect(),
+        };
+        if let Ok(bytes) = serde_json::to_vec(&manifest) {
+            let _ = fs::write(path, bytes);
+        }
+    }
+
+    /// A log entry's key is the table it mutated, scoped to its database;
+    /// later mutations to the same table supersede earlier ones on compaction.
+    fn key_for(entry: &LogEntry) -> String {
+        let table = entry
+            .details
+            .get("table")
+            .and_then(|value| value.as_str())
+            .unwrap_or("");
+        format!("{}:{}", entry.db, table)
+    }
+
+    /// Record a mutation event, flushing the memtable to a new SSTable once
+    /// it grows past the threshold.
     pub fn log(&mut self, entry: LogEntry) {
-        self.segments.push(entry);
+        self.memtable.push(entry);
+        if self.memtable.len() > MEMTABLE_LIMIT {
+            self.flush_memtable();
+            self.maybe_compact();
+        }
+    }
+
+    fn flush_memtable(&mut self) {
+        let dir = match &self.dir {
+            Some(dir) => dir.clone(),
+            None => return,
+        };
+        if self.memtable.is_empty() {
+            return;
+        }
+        let entries: Vec<(String, LogEntry)> = self
+            .memtable
+            .drain(..)
+            .map(|entry| (Self::key_for(&entry), entry))
+            .collect();
+        let file = dir.join(format!("{:06}.sst", self.next_segment_id));
+        self.next_segment_id += 1;
+        if let Some(sstable) = SSTable::flush(file, 0, entries) {
+            self.sstables.push(sstable);
+            self.write_manifest();
+        }
     }
 
-    /// Return the number of uncommitted entries.
+    /// Cascade size-tiered compaction up through the levels: once a level's
+    /// segments together hold more entries than its tier capacity, merge
+    /// all of them into one segment one level up, keeping only the newest
+    /// entry per key and dropping keys whose newest entry is a DELETE
+    /// tombstone. Repeats on the level just fed in case it now overflows too.
+    fn maybe_compact(&mut self) {
+        let mut level = 0u32;
+        loop {
+            let segments_at_level: Vec<usize> = self
+                .sstables
+                .iter()
+                .enumerate()
+                .filter(|(_, sstable)| sstable.level == level)
+                .map(|(index, _)| index)
+                .collect();
+            if segments_at_level.len() < 2 {
+                return;
+            }
+            let total_entries: usize = segments_at_level
+                .iter()
+                .map(|&index| self.sstables[index].all_entries().len())
+                .sum();
+            if total_entries <= tier_capacity(level) {
+                return;
+            }
+
+            let dir = match &self.dir {
+                Some(dir) => dir.clone(),
+                None => return,
+            };
+            let mut merged: HashMap<String, LogEntry> = HashMap::new();
+            for &index in &segments_at_level {
+                for (key, entry) in self.sstables[index].all_entries() {
+                    merged.insert(key, entry);
+                }
+            }
+            let entries: Vec<(String, LogEntry)> = merged
+                .into_iter()
+                .filter(|(_, entry)| entry.command != "DELETE")
+                .collect();
+
+            let file = dir.join(format!("{:06}.sst", self.next_segment_id));
+            self.next_segment_id += 1;
+            let merged_sstable = SSTable::flush(file, level + 1, entries);
+
+            self.sstables = self
+                .sstables
+                .drain(..)
+                .enumerate()
+                .filter(|(index, _)| !segments_at_level.contains(index))
+                .map(|(_, sstable)| sstable)
+                .collect();
+            if let Some(merged_sstable) = merged_sstable {
+                self.sstables.push(merged_sstable);
+            }
+            self.write_manifest();
+
+            level += 1;
+        }
+    }
+
+    /// Look up the most recent mutation recorded for a table: the memtable
+    /// first, then SSTables from newest to oldest.
+    pub fn get(&self, db: &str, table: &str) -> Option<LogEntry> {
+        let key = format!("{}:{}", db, table);
+        if let Some(entry) = self
+            .memtable
+            .iter()
+            .rev()
+            .find(|entry| Self::key_for(entry) == key)
+        {
+            return Some(entry.clone());
+        }
+        self.sstables.iter().rev().find_map(|sstable| sstable.get(&key))
+    }
+
+    /// Return the number of uncommitted entries still in the memtable.
     pub fn pending(&self) -> usize {
-        self.segments.len()
+        self.memtable.len()
     }
 
-    /// Return a copy of the current pending entries.
+    /// Return a copy of the current pending (not yet flushed) entries.
     pub fn snapshot(&self) -> Vec<LogEntry> {
-        self.segments.clone()
+        self.memtable.clone()
     }
 
-    /// Flush all pending entries and compact the log.
+    /// Mark a durable checkpoint: force whatever is still in the memtable
+    /// out to an SSTable regardless of the size threshold, then return a
+    /// copy of the entries that were just made durable.
     pub fn commit(&mut self) -> Vec<LogEntry> {
-        let flushed = self.segments.clone();
-        self.segments.clear();
-        self.compact();
+        let flushed = self.memtable.clone();
+        if self.dir.is_some() {
+            self.flush_memtable();
+            self.maybe_compact();
+        } else {
+            self.memtable.clear();
+        }
         flushed
     }
 
-    /// Retain only a limited window of committed history.
-    fn compact(&mut self) {
-        if self.segments.len() > 10 {
-            self.segments = self.segments[self.segments.len() - 10..].to_vec();
-        }
+    /// Every mutation event recorded for a database: the still-live
+    /// SSTables followed by whatever hasn't been flushed out of the
+    /// memtable yet. A best-effort fallback for reconstructing table state
+    /// when no `.dat` snapshot survived — once compaction has folded a
+    /// table's history down to its newest entry, this can no longer replay
+    /// every row, so it is not a substitute for the snapshot, only a
+    /// last-resort recovery path for a database that lost it entirely.
+    pub fn entries_for(&self, db: &str) -> Vec<LogEntry> {
+        let mut entries: Vec<LogEntry> = self
+            .sstables
+            .iter()
+            .flat_map(|sstable| sstable.all_entries())
+            .map(|(_key, entry)| entry)
+            .filter(|entry| entry.db == db)
+            .collect();
+        entries.extend(self.memtable.iter().filter(|entry| entry.db == db).cloned());
+        entries
     }
 }
 
@@ -57,3 +290,8 @@ impl Default for LSMTreeStorage {
         Self::new()
     }
 }
+
+/// Parse the zero-padded numeric id out of a segment file name like `"000003.sst"`.
+fn segment_id(file_name: &str) -> Option<usize> {
+    file_name.split('.').next()?.parse().ok()
+}
@@ -0,0 +1,98 @@
+/// Pure in-memory `StorageEngine`: no pager, no `.dat` file; table state is
+/// lost once the process exits. Useful for scratch databases or tests.
+use crate::core::parser::{Expr, JoinInfo, Value};
+use crate::core::storage::storage_engine::StorageEngine;
+use crate::core::storage::table_store::{Row, TableStore};
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct MemoryStorage {
+    store: TableStore,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        MemoryStorage::default()
+    }
+}
+
+impl StorageEngine for MemoryStorage {
+    fn table_exists(&self, name: &str) -> bool {
+        self.store.table_exists(name)
+    }
+
+    fn columns(&self, name: &str) -> Option<Vec<String>> {
+        self.store.columns(name)
+    }
+
+    fn create_table(&mut self, name: &str, columns: Vec<String>) {
+        self.store.create_table(name, columns);
+    }
+
+    fn drop_table(&mut self, name: &str) {
+        self.store.drop_table(name);
+    }
+
+    fn add_column(&mut self, name: &str, column: String) {
+        self.store.add_column(name, column);
+    }
+
+    fn create_index(&mut self, table_name: &str, column: &str) {
+        self.store.create_index(table_name, column);
+    }
+
+    fn drop_index(&mut self, table_name: &str, column: &str) {
+        self.store.drop_index(table_name, column);
+    }
+
+    fn insert_row(&mut self, table_name: &str, values: Vec<Value>) -> Result<Row, String> {
+        self.store.insert_row(table_name, values)
+    }
+
+    fn insert_rows(&mut self, table_name: &str, values_list: Vec<Vec<Value>>) -> Result<Vec<Row>, String> {
+        self.store.insert_rows(table_name, values_list)
+    }
+
+    fn update_rows(
+        &mut self,
+        table_name: &str,
+        assignments: &HashMap<String, Value>,
+        condition: Option<&Expr>,
+    ) -> Result<usize, String> {
+        self.store.update_rows(table_name, assignments, condition)
+    }
+
+    fn delete_rows(&mut self, table_name: &str, condition: Option<&Expr>) -> Result<usize, String> {
+        self.store.delete_rows(table_name, condition)
+    }
+
+    fn delete_rows_by_ids(&mut self, table_name: &str, ids: &[usize]) -> Result<usize, String> {
+        self.store.delete_rows_by_ids(table_name, ids)
+    }
+
+    fn select_rows(
+        &self,
+        table_name: &str,
+        columns: &[String],
+        condition: Option<&Expr>,
+        joins: &[JoinInfo],
+    ) -> Result<Vec<Row>, String> {
+        self.store.select_rows(table_name, columns, condition, joins)
+    }
+
+    fn describe(&self) -> HashMap<String, HashMap<String, serde_json::Value>> {
+        self.store.describe()
+    }
+
+    fn begin_txn(&mut self) {
+        self.store.begin_txn();
+    }
+
+    fn commit_txn(&mut self) {
+        self.store.commit_txn();
+    }
+
+    fn rollback_txn(&mut self) {
+        self.store.rollback_txn();
+    }
+}
@@ -1,9 +1,12 @@
 /// Disk-backed pager persisting fixed-size pages in a .dat file.
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::fs;
 use std::path::PathBuf;
 
+mod mmap_backend;
+use mmap_backend::MmapRegion;
+
 const MAGIC: &[u8; 4] = b"MDB1";
 const HEADER_SIZE: usize = 16;
 
@@ -12,6 +15,10 @@ pub struct Pager {
     path: PathBuf,
     pages: Vec<Vec<u8>>,
     length: usize,
+    /// Present only in mmap-backed mode; maps the `.dat` payload region.
+    mmap: Option<MmapRegion>,
+    /// Page indices written since the last flush, in mmap-backed mode.
+    dirty: HashSet<usize>,
 }
 
 impl Pager {
@@ -25,15 +32,41 @@ impl Pager {
             path,
             pages: Vec::new(),
             length: 0,
+            mmap: None,
+            dirty: HashSet::new(),
+        };
+        pager.load();
+        pager
+    }
+
+    /// Open a memory-mapped pager: the `.dat` payload region is mapped into
+    /// the process and `write_page` only marks pages dirty instead of
+    /// rewriting the whole file, so `flush()` can do O(dirty pages) I/O
+    /// instead of O(pages).
+    pub fn new_mmap<P: Into<PathBuf>>(path: P, page_size: usize) -> Self {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let mut pager = Pager {
+            page_size,
+            path,
+            pages: Vec::new(),
+            length: 0,
+            mmap: None,
+            dirty: HashSet::new(),
         };
         pager.load();
+        pager.mmap = MmapRegion::open(&pager.path, HEADER_SIZE, pager.pages.len(), page_size).ok();
         pager
     }
 
-    /// Allocate a fresh zeroed page and return its index.
+    /// Allocate a new page, growing the file by one page.
     pub fn allocate_page(&mut self) -> usize {
         self.pages.push(vec![0; self.page_size]);
-        self.pages.len() - 1
+        let index = self.pages.len() - 1;
+        self.grow_mapping_if_needed();
+        index
     }
 
     /// Write data to an existing page, truncating if necessary.
@@ -41,9 +74,57 @@ impl Pager {
         while index >= self.pages.len() {
             self.pages.push(vec![0; self.page_size]);
         }
+        self.grow_mapping_if_needed();
         let len = data.len().min(self.page_size);
         self.pages[index][..len].copy_from_slice(&data[..len]);
-        self.flush();
+
+        if let Some(mmap) = &mut self.mmap {
+            mmap.write_page(index, &self.pages[index]);
+            self.dirty.insert(index);
+        } else {
+            self.flush();
+        }
+    }
+
+    /// Remap in power-of-two page-count chunks when `pages` has grown past
+    /// what is currently mapped, so growth amortizes remaps instead of
+    /// remapping on every single new page. Used by `write_page`'s
+    /// one-page-at-a-time growth (e.g. an SSTable appending entries); never
+    /// shrinks, since that caller never removes pages either.
+    fn grow_mapping_if_needed(&mut self) {
+        if let Some(mmap) = &self.mmap {
+            if self.pages.len() <= mmap.mapped_pages() {
+                return;
+            }
+            let mut capacity = mmap.mapped_pages().max(1);
+            while capacity < self.pages.len() {
+                capacity *= 2;
+            }
+            if let Ok(remapped) =
+                MmapRegion::open(&self.path, HEADER_SIZE, capacity, self.page_size)
+            {
+                self.mmap = Some(remapped);
+            }
+        }
+    }
+
+    /// Remap to exactly `pages.len()`, growing or shrinking as needed.
+    /// `write_blob` replaces the whole page list in one call rather than
+    /// growing it one page at a time, so there's no repeated-remap cost to
+    /// amortize the way `grow_mapping_if_needed` does — remapping exactly
+    /// to fit means a snapshot that shrank (e.g. after a `DropTable` or a
+    /// bulk delete) actually shrinks the mapped region and, per
+    /// `MmapRegion::open`'s `ftruncate`, the file on disk, instead of
+    /// leaving it at its largest-ever size.
+    fn resize_mapping_exact(&mut self) {
+        if self.mmap.is_none() {
+            return;
+        }
+        if let Ok(remapped) =
+            MmapRegion::open(&self.path, HEADER_SIZE, self.pages.len().max(1), self.page_size)
+        {
+            self.mmap = Some(remapped);
+        }
     }
 
     /// Return the bytes stored at a page index.
@@ -55,6 +136,7 @@ impl Pager {
         self.length = data.len();
         if data.is_empty() {
             self.pages.clear();
+            self.resize_mapping_exact();
             self.flush();
             return;
         }
@@ -67,6 +149,16 @@ impl Pager {
             page[..end - start].copy_from_slice(&data[start..end]);
             self.pages.push(page);
         }
+        self.resize_mapping_exact();
+        if self.mmap.is_some() {
+            for index in 0..self.pages.len() {
+                let page = self.pages[index].clone();
+                if let Some(mmap) = &mut self.mmap {
+                    mmap.write_page(index, &page);
+                }
+                self.dirty.insert(index);
+            }
+        }
         self.flush();
     }
 
@@ -82,11 +174,14 @@ impl Pager {
         buffer
     }
 
-    /// Provide simple pager statistics.
+    /// Provide simple pager statistics, including write amplification: in
+    /// mmap-backed mode `dirty_pages` is the count flushed out of `pages` on
+    /// the next `flush()`, versus the whole-file rewrite of the plain mode.
     pub fn stats(&self) -> HashMap<String, usize> {
         let mut stats = HashMap::new();
         stats.insert("pages".to_string(), self.pages.len());
         stats.insert("page_size".to_string(), self.page_size);
+        stats.insert("dirty_pages".to_string(), self.dirty.len());
         stats
     }
 
@@ -112,7 +207,17 @@ impl Pager {
         }
     }
 
-    fn flush(&self) {
+    /// In mmap-backed mode, only the header and the pages marked dirty since
+    /// the last flush are synced; the plain mode still rewrites the whole
+    /// page vector, since it holds no file mapping to patch in place.
+    fn flush(&mut self) {
+        if let Some(mmap) = &mut self.mmap {
+            mmap.write_header(MAGIC, self.page_size, self.length);
+            mmap.flush_range(self.dirty.iter().copied());
+            self.dirty.clear();
+            return;
+        }
+
         if self.pages.is_empty() {
             let _ = fs::write(&self.path, &[] as &[u8]);
             return;
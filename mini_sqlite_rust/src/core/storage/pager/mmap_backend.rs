@@ -0,0 +1,193 @@
+/// Minimal memory-mapped file backing `Pager`'s mmap mode, hand-rolled
+/// against raw libc calls rather than pulling in a mmap crate, matching the
+/// rest of this module's dependency-free I/O.
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+
+#[cfg(unix)]
+mod ffi {
+    use std::os::raw::{c_int, c_void};
+
+    pub const PROT_READ: c_int = 1;
+    pub const PROT_WRITE: c_int = 2;
+    pub const MAP_SHARED: c_int = 1;
+    pub const MS_SYNC: c_int = 4;
+
+    extern "C" {
+        pub fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: i64,
+        ) -> *mut c_void;
+        pub fn munmap(addr: *mut c_void, len: usize) -> c_int;
+        pub fn msync(addr: *mut c_void, len: usize, flags: c_int) -> c_int;
+        pub fn ftruncate(fd: c_int, length: i64) -> c_int;
+    }
+}
+
+#[cfg(unix)]
+pub struct MmapRegion {
+    ptr: *mut u8,
+    header_size: usize,
+    page_size: usize,
+    mapped_pages: usize,
+    _file: std::fs::File,
+}
+
+#[cfg(unix)]
+impl MmapRegion {
+    /// Map exactly `min_pages` worth of payload (plus the fixed header)
+    /// from `path`, resizing the backing file first (growing or truncating)
+    /// if its current length doesn't already match.
+    pub fn open(
+        path: &Path,
+        header_size: usize,
+        min_pages: usize,
+        page_size: usize,
+    ) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let pages = min_pages.max(1);
+        let map_len = header_size + pages * page_size;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)?;
+        let current_len = file.metadata()?.len();
+        if current_len as usize != map_len {
+            let rc = unsafe { ffi::ftruncate(file.as_raw_fd(), map_len as i64) };
+            if rc != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        let ptr = unsafe {
+            ffi::mmap(
+                std::ptr::null_mut(),
+                map_len,
+                ffi::PROT_READ | ffi::PROT_WRITE,
+                ffi::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(MmapRegion {
+            ptr: ptr as *mut u8,
+            header_size,
+            page_size,
+            mapped_pages: pages,
+            _file: file,
+        })
+    }
+
+    pub fn mapped_pages(&self) -> usize {
+        self.mapped_pages
+    }
+
+    pub fn write_header(&mut self, magic: &[u8; 4], page_size: usize, length: usize) {
+        unsafe {
+            std::ptr::copy_nonoverlapping(magic.as_ptr(), self.ptr, 4);
+            std::ptr::copy_nonoverlapping(
+                (page_size as u32).to_le_bytes().as_ptr(),
+                self.ptr.add(4),
+                4,
+            );
+            std::ptr::copy_nonoverlapping(
+                (length as u64).to_le_bytes().as_ptr(),
+                self.ptr.add(8),
+                8,
+            );
+        }
+    }
+
+    pub fn write_page(&mut self, index: usize, data: &[u8]) {
+        if index >= self.mapped_pages {
+            return;
+        }
+        let offset = self.header_size + index * self.page_size;
+        let len = data.len().min(self.page_size);
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.ptr.add(offset), len);
+            if len < self.page_size {
+                std::ptr::write_bytes(self.ptr.add(offset + len), 0, self.page_size - len);
+            }
+        }
+    }
+
+    /// Sync the header plus every dirty page's byte range, instead of the
+    /// whole mapping.
+    pub fn flush_range(&self, dirty: impl Iterator<Item = usize>) {
+        unsafe {
+            ffi::msync(self.ptr as *mut _, self.header_size, ffi::MS_SYNC);
+        }
+        for index in dirty {
+            if index >= self.mapped_pages {
+                continue;
+            }
+            let offset = self.header_size + index * self.page_size;
+            unsafe {
+                ffi::msync(self.ptr.add(offset) as *mut _, self.page_size, ffi::MS_SYNC);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for MmapRegion {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::munmap(
+                self.ptr as *mut _,
+                self.header_size + self.mapped_pages * self.page_size,
+            );
+        }
+    }
+}
+
+// `ptr` is `MmapRegion`'s exclusive handle to a `MAP_SHARED` mapping it owns
+// outright (the backing file is held alongside it and closed together with
+// the mapping on drop). Nothing else in the process holds a copy of the raw
+// pointer, so moving a `MmapRegion` to another thread just moves that
+// ownership with it; `&mut self` methods already require exclusive access,
+// which callers serialize through `Pager`'s own locking. That's exactly what
+// `Send` asserts, so it's safe to opt in by hand for the raw pointer field.
+#[cfg(unix)]
+unsafe impl Send for MmapRegion {}
+
+#[cfg(not(unix))]
+pub struct MmapRegion;
+
+#[cfg(not(unix))]
+impl MmapRegion {
+    pub fn open(
+        _path: &Path,
+        _header_size: usize,
+        _min_pages: usize,
+        _page_size: usize,
+    ) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "memory-mapped pager requires a unix target",
+        ))
+    }
+
+    pub fn mapped_pages(&self) -> usize {
+        0
+    }
+
+    pub fn write_header(&mut self, _magic: &[u8; 4], _page_size: usize, _length: usize) {}
+
+    pub fn write_page(&mut self, _index: usize, _data: &[u8]) {}
+
+    pub fn flush_range(&self, _dirty: impl Iterator<Item = usize>) {}
+}
@@ -0,0 +1,117 @@
+/// On-disk SSTable segments for `LSMTreeStorage`: an immutable, key-sorted
+/// run of entries split into fixed-size pages via `Pager`, plus a small
+/// in-memory index mapping each page's first key to its page number so a
+/// lookup can binary-search straight to the right page.
+use crate::core::storage::lsm_tree::LogEntry;
+use crate::core::storage::pager::Pager;
+use serde_json::{from_slice, to_vec};
+use std::path::PathBuf;
+
+/// Large enough that a page of a handful of JSON-encoded `LogEntry` values
+/// comfortably fits without the pager's fixed-size write silently truncating it.
+const SSTABLE_PAGE_SIZE: usize = 65536;
+const ENTRIES_PER_PAGE: usize = 8;
+
+pub struct SSTable {
+    pub level: u32,
+    path: PathBuf,
+    pager: Pager,
+    /// (first key on the page, page index), sorted by key for binary search.
+    index: Vec<(String, usize)>,
+}
+
+impl SSTable {
+    /// Freeze a memtable into a new immutable, key-sorted segment.
+    pub fn flush(path: PathBuf, level: u32, mut entries: Vec<(String, LogEntry)>) -> Option<Self> {
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut pager = Pager::new(path.clone(), SSTABLE_PAGE_SIZE);
+        let mut index = Vec::new();
+
+        for chunk in entries.chunks(ENTRIES_PER_PAGE) {
+            let first_key = chunk.first()?.0.clone();
+            let page_index = pager.allocate_page();
+            let bytes = to_vec(chunk).ok()?;
+            if bytes.len() > SSTABLE_PAGE_SIZE {
+                // A page overflowed its budget; this segment is unusable.
+                return None;
+            }
+            pager.write_page(page_index, &bytes);
+            index.push((first_key, page_index));
+        }
+
+        Some(SSTable {
+            level,
+            path,
+            pager,
+            index,
+        })
+    }
+
+    /// Reopen a segment file previously listed in the manifest, rebuilding
+    /// its index by reading every page back.
+    pub fn open(path: PathBuf, level: u32) -> Option<Self> {
+        let pager = Pager::new(path.clone(), SSTABLE_PAGE_SIZE);
+        let mut index = Vec::new();
+        let mut page_index = 0;
+        while let Some(bytes) = pager.read_page(page_index) {
+            if let Ok(chunk) = from_slice::<Vec<(String, LogEntry)>>(trim_zero_tail(bytes)) {
+                if let Some((key, _)) = chunk.first() {
+                    index.push((key.clone(), page_index));
+                }
+            }
+            page_index += 1;
+        }
+        Some(SSTable {
+            level,
+            path,
+            pager,
+            index,
+        })
+    }
+
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Binary-search the page index, then scan that page from its end so the
+    /// last (newest) entry written for `key` is the one returned.
+    pub fn get(&self, key: &str) -> Option<LogEntry> {
+        let page_index = match self.index.partition_point(|(k, _)| k.as_str() <= key) {
+            0 => return None,
+            n => self.index[n - 1].1,
+        };
+        let bytes = self.pager.read_page(page_index)?;
+        let chunk: Vec<(String, LogEntry)> = from_slice(trim_zero_tail(bytes)).ok()?;
+        chunk
+            .into_iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, entry)| entry)
+    }
+
+    /// Every (key, entry) pair on this segment, in sorted order, used by
+    /// compaction to merge segments together.
+    pub fn all_entries(&self) -> Vec<(String, LogEntry)> {
+        let mut all = Vec::new();
+        for &(_, page_index) in &self.index {
+            if let Some(bytes) = self.pager.read_page(page_index) {
+                if let Ok(chunk) = from_slice::<Vec<(String, LogEntry)>>(trim_zero_tail(bytes)) {
+                    all.extend(chunk);
+                }
+            }
+        }
+        all
+    }
+}
+
+/// Pages are zero-padded up to `SSTABLE_PAGE_SIZE`; JSON parses happily off
+/// the front, but trimming first avoids handing serde a page's worth of
+/// trailing NUL bytes it needs to scan past.
+fn trim_zero_tail(bytes: &[u8]) -> &[u8] {
+    let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    &bytes[..end]
+}
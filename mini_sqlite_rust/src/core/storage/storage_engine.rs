@@ -0,0 +1,51 @@
+/// Storage-engine abstraction so each database can pick its own backend
+/// (disk-backed btree, pure in-memory, ...) behind one interface.
+use crate::core::parser::{Expr, JoinInfo, Value};
+use crate::core::storage::table_store::Row;
+use std::collections::HashMap;
+
+pub trait StorageEngine: Send {
+    fn table_exists(&self, name: &str) -> bool;
+    fn columns(&self, name: &str) -> Option<Vec<String>>;
+    fn create_table(&mut self, name: &str, columns: Vec<String>);
+    fn drop_table(&mut self, name: &str);
+    fn add_column(&mut self, name: &str, column: String);
+    fn create_index(&mut self, table_name: &str, column: &str);
+    fn drop_index(&mut self, table_name: &str, column: &str);
+    fn insert_row(&mut self, table_name: &str, values: Vec<Value>) -> Result<Row, String>;
+    /// Insert several rows in one call: affected indexes are rebuilt once for
+    /// the whole batch instead of once per row, and disk-backed engines
+    /// persist only once at the end instead of once per `insert_row` call.
+    fn insert_rows(&mut self, table_name: &str, values_list: Vec<Vec<Value>>) -> Result<Vec<Row>, String>;
+    fn update_rows(
+        &mut self,
+        table_name: &str,
+        assignments: &HashMap<String, Value>,
+        condition: Option<&Expr>,
+    ) -> Result<usize, String>;
+    fn delete_rows(&mut self, table_name: &str, condition: Option<&Expr>) -> Result<usize, String>;
+    /// Delete rows by their position in the table's stored row list, rebuilding
+    /// affected indexes and persisting once for the whole batch.
+    fn delete_rows_by_ids(&mut self, table_name: &str, ids: &[usize]) -> Result<usize, String>;
+    fn select_rows(
+        &self,
+        table_name: &str,
+        columns: &[String],
+        condition: Option<&Expr>,
+        joins: &[JoinInfo],
+    ) -> Result<Vec<Row>, String>;
+    fn describe(&self) -> HashMap<String, HashMap<String, serde_json::Value>>;
+
+    /// Open a transaction: writes still land in `self.tables` immediately
+    /// (so statements inside the transaction see each other's effects) but
+    /// an engine may use this to defer durability work, e.g. a single
+    /// `persist()` at `commit_txn` instead of one per statement. The
+    /// default is a no-op, appropriate for engines with nothing to defer.
+    fn begin_txn(&mut self) {}
+
+    /// Make the transaction's writes durable.
+    fn commit_txn(&mut self) {}
+
+    /// Undo every write made since `begin_txn`, restoring the pre-transaction state.
+    fn rollback_txn(&mut self) {}
+}
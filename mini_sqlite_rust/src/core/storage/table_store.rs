@@ -0,0 +1,908 @@
+/// Shared row/table bookkeeping used by every `StorageEngine` implementation,
+/// independent of how (or whether) a database persists to disk.
+use crate::core::parser::{CompareOp, Expr, JoinInfo, JoinType, Value};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound::{Excluded, Included, Unbounded};
+
+pub type Row = HashMap<String, Value>;
+type Index = HashMap<String, Vec<usize>>;
+type OrderedIndex = BTreeMap<OrderedKey, Vec<usize>>;
+
+/// Total order over `Value`s: `Integer`/`Float` compare numerically (mixed
+/// pairs via `f64`), `Text` lexicographically, `Null` sorts lowest, and any
+/// other cross-type pair falls back to comparing a fixed type rank so the
+/// order stays total even though `Value` has no natural `Ord`.
+pub fn values_compare(a: &Value, b: &Value) -> Ordering {
+    fn rank(v: &Value) -> u8 {
+        match v {
+            Value::Null => 0,
+            Value::Integer(_) | Value::Float(_) => 1,
+            Value::Text(_) => 2,
+            // Never stored in a row; ranked last only so the match stays exhaustive.
+            Value::Placeholder(_) | Value::Param(_) | Value::Column(_) => 3,
+        }
+    }
+    match (a, b) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Integer(x), Value::Integer(y)) => x.cmp(y),
+        (Value::Float(x), Value::Float(y)) => x.partial_cmp(y).unwrap_or(Ordering::Equal),
+        (Value::Integer(x), Value::Float(y)) => (*x as f64).partial_cmp(y).unwrap_or(Ordering::Equal),
+        (Value::Float(x), Value::Integer(y)) => x.partial_cmp(&(*y as f64)).unwrap_or(Ordering::Equal),
+        (Value::Text(x), Value::Text(y)) => x.cmp(y),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// A `Value` wrapper with a total `Ord`, so it can key a `BTreeMap` and
+/// support `range()` scans for comparison predicates.
+#[derive(Clone, Debug)]
+pub struct OrderedKey(Value);
+
+impl PartialEq for OrderedKey {
+    fn eq(&self, other: &Self) -> bool {
+        values_compare(&self.0, &other.0) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedKey {}
+
+impl PartialOrd for OrderedKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        values_compare(&self.0, &other.0)
+    }
+}
+
+#[derive(Clone)]
+pub struct TableMeta {
+    pub columns: Vec<String>,
+    pub rows: Vec<Row>,
+    pub indexes: HashMap<String, Index>,
+    /// Same row membership as `indexes`, but `BTreeMap`-backed so `rows_for`
+    /// can answer `<`/`<=`/`>`/`>=`/`BETWEEN` with a `range()` scan instead
+    /// of a full table scan.
+    pub ordered_indexes: HashMap<String, OrderedIndex>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TableSnapshot {
+    pub columns: Vec<String>,
+    pub rows: Vec<Row>,
+    pub indexes: Vec<String>,
+}
+
+/// In-memory table state plus the row/index/join operations every storage
+/// engine needs. Engines differ only in whether (and how) this gets synced
+/// to disk, which is handled by the engine wrapper, not here.
+#[derive(Default)]
+pub struct TableStore {
+    pub tables: HashMap<String, TableMeta>,
+    /// While a transaction is open, the pre-transaction state of every
+    /// table touched so far, keyed by table name; `None` means the table
+    /// did not exist yet. `rollback_txn` restores exactly this state.
+    txn_snapshots: Option<HashMap<String, Option<TableMeta>>>,
+}
+
+impl TableStore {
+    pub fn new() -> Self {
+        TableStore::default()
+    }
+
+    /// Start snapshotting tables on first write so `rollback_txn` can undo them.
+    pub fn begin_txn(&mut self) {
+        self.txn_snapshots = Some(HashMap::new());
+    }
+
+    /// Durably keep the transaction's writes; nothing left to undo.
+    pub fn commit_txn(&mut self) {
+        self.txn_snapshots = None;
+    }
+
+    /// Restore every touched table to its pre-transaction state.
+    pub fn rollback_txn(&mut self) {
+        if let Some(snapshots) = self.txn_snapshots.take() {
+            for (name, prior) in snapshots {
+                match prior {
+                    Some(meta) => {
+                        self.tables.insert(name, meta);
+                    }
+                    None => {
+                        self.tables.remove(&name);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record a table's pre-write state the first time it is touched in an
+    /// open transaction; a no-op once a snapshot already exists for it, or
+    /// when no transaction is open.
+    fn snapshot_before_write(&mut self, table_name: &str) {
+        if let Some(snapshots) = &mut self.txn_snapshots {
+            if !snapshots.contains_key(table_name) {
+                let prior = self.tables.get(table_name).cloned();
+                snapshots.insert(table_name.to_string(), prior);
+            }
+        }
+    }
+
+    pub fn create_table(&mut self, name: &str, columns: Vec<String>) {
+        self.snapshot_before_write(name);
+        let meta = TableMeta {
+            columns,
+            rows: Vec::new(),
+            indexes: HashMap::new(),
+            ordered_indexes: HashMap::new(),
+        };
+        self.tables.insert(name.to_string(), meta);
+    }
+
+    pub fn drop_table(&mut self, name: &str) {
+        self.snapshot_before_write(name);
+        self.tables.remove(name);
+    }
+
+    pub fn table_exists(&self, name: &str) -> bool {
+        self.tables.contains_key(name)
+    }
+
+    pub fn columns(&self, name: &str) -> Option<Vec<String>> {
+        self.tables.get(name).map(|table| table.columns.clone())
+    }
+
+    pub fn add_column(&mut self, name: &str, column: String) {
+        self.snapshot_before_write(name);
+        let indexes_to_rebuild = {
+            let table = match self.tables.get_mut(name) {
+                Some(table) => table,
+                None => return,
+            };
+            if table.columns.contains(&column) {
+                return;
+            }
+            table.columns.push(column.clone());
+            for row in &mut table.rows {
+                row.insert(column.clone(), Value::Null);
+            }
+            table.indexes.keys().cloned().collect::<Vec<String>>()
+        };
+
+        for col in indexes_to_rebuild {
+            self.rebuild_index(name, &col);
+        }
+    }
+
+    pub fn create_index(&mut self, table_name: &str, column: &str) {
+        self.snapshot_before_write(table_name);
+        if let Some(table) = self.tables.get_mut(table_name) {
+            if !table.indexes.contains_key(column) {
+                self.rebuild_index(table_name, column);
+            }
+        }
+    }
+
+    pub fn drop_index(&mut self, table_name: &str, column: &str) {
+        self.snapshot_before_write(table_name);
+        if let Some(table) = self.tables.get_mut(table_name) {
+            table.indexes.remove(column);
+            table.ordered_indexes.remove(column);
+        }
+    }
+
+    pub fn insert_row(&mut self, table_name: &str, values: Vec<Value>) -> Result<Row, String> {
+        self.snapshot_before_write(table_name);
+        let table = self
+            .tables
+            .get_mut(table_name)
+            .ok_or_else(|| format!("Table '{}' not found", table_name))?;
+
+        if values.len() != table.columns.len() {
+            return Err("Value count does not match table schema".to_string());
+        }
+
+        let mut row = Row::new();
+        for (col, val) in table.columns.iter().zip(values.iter()) {
+            row.insert(col.clone(), val.clone());
+        }
+
+        let row_idx = table.rows.len();
+        table.rows.push(row.clone());
+
+        for (column, index) in &mut table.indexes {
+            if let Some(value) = row.get(column) {
+                let key = format!("{:?}", value);
+                index.entry(key).or_insert_with(Vec::new).push(row_idx);
+            }
+        }
+        for (column, ordered) in &mut table.ordered_indexes {
+            if let Some(value) = row.get(column) {
+                ordered
+                    .entry(OrderedKey(value.clone()))
+                    .or_insert_with(Vec::new)
+                    .push(row_idx);
+            }
+        }
+
+        Ok(row)
+    }
+
+    /// Insert several rows at once, rebuilding each affected index exactly
+    /// once instead of once per row. Validates every row against the schema
+    /// before inserting any of them, so a bad row in the batch fails clean.
+    pub fn insert_rows(
+        &mut self,
+        table_name: &str,
+        values_list: Vec<Vec<Value>>,
+    ) -> Result<Vec<Row>, String> {
+        self.snapshot_before_write(table_name);
+        let table = self
+            .tables
+            .get_mut(table_name)
+            .ok_or_else(|| format!("Table '{}' not found", table_name))?;
+
+        for values in &values_list {
+            if values.len() != table.columns.len() {
+                return Err("Value count does not match table schema".to_string());
+            }
+        }
+
+        let mut rows = Vec::with_capacity(values_list.len());
+        for values in values_list {
+            let mut row = Row::new();
+            for (col, val) in table.columns.iter().zip(values.iter()) {
+                row.insert(col.clone(), val.clone());
+            }
+            table.rows.push(row.clone());
+            rows.push(row);
+        }
+
+        let indexes_to_rebuild = table.indexes.keys().cloned().collect::<Vec<String>>();
+        for column in indexes_to_rebuild {
+            self.rebuild_index(table_name, &column);
+        }
+
+        Ok(rows)
+    }
+
+    /// Delete rows by their row index, rebuilding each affected index exactly
+    /// once instead of once per row.
+    pub fn delete_rows_by_ids(&mut self, table_name: &str, ids: &[usize]) -> Result<usize, String> {
+        self.snapshot_before_write(table_name);
+        let to_delete: std::collections::HashSet<usize> = ids.iter().copied().collect();
+
+        let (kept, deleted, index_columns) = {
+            let table = self
+                .tables
+                .get(table_name)
+                .ok_or_else(|| format!("Table '{}' not found", table_name))?;
+            let mut kept_rows = Vec::new();
+            let mut deleted_count = 0;
+            for (idx, row) in table.rows.iter().enumerate() {
+                if to_delete.contains(&idx) {
+                    deleted_count += 1;
+                } else {
+                    kept_rows.push(row.clone());
+                }
+            }
+            let columns = table.indexes.keys().cloned().collect::<Vec<String>>();
+            (kept_rows, deleted_count, columns)
+        };
+
+        let table = self.tables.get_mut(table_name).unwrap();
+        table.rows = kept;
+
+        for column in index_columns {
+            self.rebuild_index(table_name, &column);
+        }
+
+        Ok(deleted)
+    }
+
+    pub fn select_rows(
+        &self,
+        table_name: &str,
+        columns: &[String],
+        condition: Option<&Expr>,
+        joins: &[JoinInfo],
+    ) -> Result<Vec<Row>, String> {
+        if !joins.is_empty() {
+            return self.join_rows(table_name, columns, condition, joins);
+        }
+
+        if !self.tables.contains_key(table_name) {
+            return Err(format!("Table '{}' not found", table_name));
+        }
+
+        let rows: Vec<&Row> = self.rows_for(table_name, condition).collect();
+
+        if columns.len() == 1 && columns[0] == "*" {
+            return Ok(rows.iter().map(|r| (*r).clone()).collect());
+        }
+
+        let mut selected = Vec::new();
+        for row in rows {
+            let mut projected = Row::new();
+            for col in columns {
+                let lookup = col.split('.').last().unwrap_or(col);
+                projected.insert(col.clone(), row.get(lookup).cloned().unwrap_or(Value::Null));
+            }
+            selected.push(projected);
+        }
+
+        Ok(selected)
+    }
+
+    pub fn update_rows(
+        &mut self,
+        table_name: &str,
+        assignments: &HashMap<String, Value>,
+        condition: Option<&Expr>,
+    ) -> Result<usize, String> {
+        if !self.tables.contains_key(table_name) {
+            return Err(format!("Table '{}' not found", table_name));
+        }
+        self.snapshot_before_write(table_name);
+
+        let indexes_to_rebuild = self
+            .tables
+            .get(table_name)
+            .unwrap()
+            .indexes
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>();
+
+        let indices = {
+            let table = self.tables.get(table_name).unwrap();
+            if let Some(cond) = condition {
+                table
+                    .rows
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, row)| if Self::eval_expr(row, cond) { Some(idx) } else { None })
+                    .collect::<Vec<usize>>()
+            } else {
+                (0..table.rows.len()).collect::<Vec<usize>>()
+            }
+        };
+
+        {
+            let table = self.tables.get_mut(table_name).unwrap();
+            for idx in &indices {
+                if let Some(row) = table.rows.get_mut(*idx) {
+                    for (key, value) in assignments {
+                        row.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+
+        for column in indexes_to_rebuild {
+            self.rebuild_index(table_name, &column);
+        }
+
+        Ok(indices.len())
+    }
+
+    pub fn delete_rows(
+        &mut self,
+        table_name: &str,
+        condition: Option<&Expr>,
+    ) -> Result<usize, String> {
+        self.snapshot_before_write(table_name);
+        let (kept, deleted, index_columns) = {
+            let table = self
+                .tables
+                .get(table_name)
+                .ok_or_else(|| format!("Table '{}' not found", table_name))?;
+            let mut kept_rows = Vec::new();
+            let mut deleted_count = 0;
+            if let Some(cond) = condition {
+                for row in &table.rows {
+                    if Self::eval_expr(row, cond) {
+                        deleted_count += 1;
+                    } else {
+                        kept_rows.push(row.clone());
+                    }
+                }
+            } else {
+                deleted_count = table.rows.len();
+            }
+            let columns = table.indexes.keys().cloned().collect::<Vec<String>>();
+            (kept_rows, deleted_count, columns)
+        };
+
+        let table = self.tables.get_mut(table_name).unwrap();
+        table.rows = kept;
+
+        for column in index_columns {
+            self.rebuild_index(table_name, &column);
+        }
+
+        Ok(deleted)
+    }
+
+    /// Resolve the row indices an ordered index satisfies for a `<`/`<=`/
+    /// `>`/`>=` predicate via a single `range()` scan instead of walking
+    /// every entry.
+    fn ordered_range_indices(ordered: &OrderedIndex, op: CompareOp, value: &Value) -> Vec<usize> {
+        let key = OrderedKey(value.clone());
+        let bounds = match op {
+            CompareOp::Lt => (Unbounded, Excluded(key)),
+            CompareOp::Le => (Unbounded, Included(key)),
+            CompareOp::Gt => (Excluded(key), Unbounded),
+            CompareOp::Ge => (Included(key), Unbounded),
+            _ => unreachable!("only range operators reach ordered_range_indices"),
+        };
+        ordered
+            .range(bounds)
+            .flat_map(|(_, indices)| indices.iter().copied())
+            .collect()
+    }
+
+    /// Resolve a table's rows matching `condition`. A bare `Expr::Compare`
+    /// routes through the hash index (`Eq`) or ordered index (`<`/`<=`/`>`/
+    /// `>=`) when one exists for its column; any compound expression
+    /// (`And`/`Or`/`Not`/`IsNull`, or a comparison with no matching index)
+    /// falls back to a full scan evaluated by `eval_expr`.
+    fn rows_for<'a>(
+        &'a self,
+        table_name: &str,
+        condition: Option<&'a Expr>,
+    ) -> Box<dyn Iterator<Item = &'a Row> + 'a> {
+        let table = match self.tables.get(table_name) {
+            Some(t) => t,
+            None => return Box::new(std::iter::empty()),
+        };
+
+        let cond = match condition {
+            Some(cond) => cond,
+            None => return Box::new(table.rows.iter()),
+        };
+
+        // A `Value::Column` right-hand side (a same-table self comparison,
+        // e.g. `WHERE a = b`) needs `eval_expr`'s row lookup to resolve, not
+        // a literal index key — fall through to the full scan below for it.
+        if let Expr::Compare { column, op, value } = cond {
+            if !matches!(value, Value::Column(_)) {
+                if *op == CompareOp::Eq {
+                    if let Some(index) = table.indexes.get(column) {
+                        let key = format!("{:?}", value);
+                        let rows: Vec<&Row> = index
+                            .get(&key)
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|idx| table.rows.get(*idx))
+                            .collect();
+                        return Box::new(rows.into_iter());
+                    }
+                } else if matches!(op, CompareOp::Lt | CompareOp::Le | CompareOp::Gt | CompareOp::Ge) {
+                    if let Some(ordered) = table.ordered_indexes.get(column) {
+                        let rows: Vec<&Row> = Self::ordered_range_indices(ordered, *op, value)
+                            .into_iter()
+                            .filter_map(|idx| table.rows.get(idx))
+                            .collect();
+                        return Box::new(rows.into_iter());
+                    }
+                }
+            }
+        }
+
+        Box::new(table.rows.iter().filter(move |row| Self::eval_expr(row, cond)))
+    }
+
+    /// Resolve a `SELECT` with one or more chained `JOIN`s. `condition`
+    /// (the `WHERE` clause) still only narrows the base table, matching a
+    /// plain `SELECT`'s behavior; each join's own match/outer-pad condition
+    /// is `join.on`. Rows flow through the chain qualified as `table.column`
+    /// the whole way, so a later join's `ON` can reference an earlier
+    /// join's columns, and so can the final projection.
+    fn join_rows(
+        &self,
+        left_table_name: &str,
+        columns: &[String],
+        condition: Option<&Expr>,
+        joins: &[JoinInfo],
+    ) -> Result<Vec<Row>, String> {
+        let left_table = self
+            .tables
+            .get(left_table_name)
+            .ok_or_else(|| format!("Table '{}' not found", left_table_name))?;
+
+        let mut combined: Vec<Row> = self
+            .rows_for(left_table_name, condition)
+            .map(|row| Self::qualify_row(left_table_name, &left_table.columns, row))
+            .collect();
+
+        let mut known_tables = vec![left_table_name.to_string()];
+        for join in joins {
+            combined = self.apply_join(combined, &known_tables, join)?;
+            known_tables.push(join.table.clone());
+        }
+
+        if columns.len() == 1 && columns[0] == "*" {
+            return Ok(combined);
+        }
+
+        let mut selected = Vec::new();
+        for row in &combined {
+            let mut projected = Row::new();
+            for col in columns {
+                if col.contains('.') {
+                    projected.insert(col.clone(), row.get(col).cloned().unwrap_or(Value::Null));
+                } else {
+                    let qualified = known_tables.iter().find_map(|t| {
+                        self.tables
+                            .get(t)
+                            .filter(|table| table.columns.contains(col))
+                            .map(|_| format!("{}.{}", t, col))
+                    });
+                    let value = qualified.and_then(|key| row.get(&key).cloned()).unwrap_or(Value::Null);
+                    projected.insert(col.clone(), value);
+                }
+            }
+            selected.push(projected);
+        }
+
+        Ok(selected)
+    }
+
+    /// Qualify a base row's columns as `table.column`, matching the shape
+    /// joined rows carry throughout `join_rows`.
+    fn qualify_row(table_name: &str, table_columns: &[String], row: &Row) -> Row {
+        let mut qualified = Row::new();
+        for col in table_columns {
+            qualified.insert(
+                format!("{}.{}", table_name, col),
+                row.get(col).cloned().unwrap_or(Value::Null),
+            );
+        }
+        qualified
+    }
+
+    /// Join `left_rows` (already qualified `table.column` rows from the base
+    /// table and every prior join) against `join.table`. A plain
+    /// `<column> = <column>` equality naming `join.table` on one side probes
+    /// a hash index the same way a bare single-column join always has —
+    /// `O(left + right)` instead of scanning every right row per left row.
+    /// Anything else `on` could be (an `AND`/`OR`-chained condition, a
+    /// non-equality comparison) falls back to a nested-loop scan evaluating
+    /// the full expression via `eval_expr`. `Left`/`Full` joins NULL-pad
+    /// `join.table`'s columns for a left row with no match; `Right`/`Full`
+    /// joins do the same for the earlier tables' columns on an unmatched
+    /// right row, built from `known_tables`' schemas alone (so it works even
+    /// when `left_rows` is empty).
+    fn apply_join(&self, left_rows: Vec<Row>, known_tables: &[String], join: &JoinInfo) -> Result<Vec<Row>, String> {
+        let right_table = self
+            .tables
+            .get(&join.table)
+            .ok_or_else(|| format!("Table '{}' not found", join.table))?;
+
+        let (mut result, right_matched) = match Self::equi_join_columns(&join.on, &join.table) {
+            Some((left_key, right_col)) => Self::probe_equi_join(&left_rows, join, right_table, left_key, right_col),
+            None => Self::scan_join(&left_rows, join, right_table),
+        };
+
+        if matches!(join.join_type, JoinType::Right | JoinType::Full) {
+            let mut null_left = Row::new();
+            for table_name in known_tables {
+                if let Some(table) = self.tables.get(table_name) {
+                    for col in &table.columns {
+                        null_left.insert(format!("{}.{}", table_name, col), Value::Null);
+                    }
+                }
+            }
+            for (idx, matched) in right_matched.iter().enumerate() {
+                if *matched {
+                    continue;
+                }
+                let mut padded = null_left.clone();
+                for col in &right_table.columns {
+                    padded.insert(
+                        format!("{}.{}", join.table, col),
+                        right_table.rows[idx].get(col).cloned().unwrap_or(Value::Null),
+                    );
+                }
+                result.push(padded);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// If `on` is a plain `<column> = <column>` equality where exactly one
+    /// side's table qualifier is `right_table_name` and the other isn't,
+    /// return `(other_side_key, right_table_bare_column)` — the key to look
+    /// a left row up by, and the bare column name to probe `right_table`'s
+    /// index with. `None` for anything else (a compound `AND`/`OR`
+    /// condition, a non-equality comparison, an equality against a literal),
+    /// since those can't be reduced to a single hash probe.
+    fn equi_join_columns<'a>(on: &'a Expr, right_table_name: &str) -> Option<(&'a str, &'a str)> {
+        let (column, value) = match on {
+            Expr::Compare {
+                column,
+                op: CompareOp::Eq,
+                value,
+            } => (column.as_str(), value),
+            _ => return None,
+        };
+        let other_column = match value {
+            Value::Column(name) => name.as_str(),
+            _ => return None,
+        };
+
+        fn table_of(qualified: &str) -> Option<&str> {
+            qualified.split('.').next()
+        }
+        fn bare(qualified: &str) -> &str {
+            qualified.split('.').last().unwrap_or(qualified)
+        }
+
+        if table_of(column) == Some(right_table_name) && table_of(other_column) != Some(right_table_name) {
+            Some((other_column, bare(column)))
+        } else if table_of(other_column) == Some(right_table_name) && table_of(column) != Some(right_table_name) {
+            Some((column, bare(other_column)))
+        } else {
+            None
+        }
+    }
+
+    /// Hash-index fast path: probe `right_table`'s existing index on
+    /// `right_col` (or one built here on the fly, the same way a bare
+    /// single-column join always has) for each left row's `left_key` value,
+    /// instead of scanning every right row. Also applies `Left`/`Full`
+    /// padding for a left row with no match; the caller applies the
+    /// matching `Right`/`Full` padding from the returned match bitmap.
+    fn probe_equi_join(
+        left_rows: &[Row],
+        join: &JoinInfo,
+        right_table: &TableMeta,
+        left_key: &str,
+        right_col: &str,
+    ) -> (Vec<Row>, Vec<bool>) {
+        let owned_index;
+        let right_index: &Index = if let Some(index) = right_table.indexes.get(right_col) {
+            index
+        } else {
+            let mut index = Index::new();
+            for (idx, row) in right_table.rows.iter().enumerate() {
+                if let Some(value) = row.get(right_col) {
+                    let key = format!("{:?}", value);
+                    index.entry(key).or_insert_with(Vec::new).push(idx);
+                }
+            }
+            owned_index = index;
+            &owned_index
+        };
+
+        let mut result = Vec::new();
+        let mut right_matched = vec![false; right_table.rows.len()];
+
+        for left_row in left_rows {
+            let mut any_match = false;
+            if let Some(key_value) = left_row.get(left_key) {
+                let key = format!("{:?}", key_value);
+                if let Some(indices) = right_index.get(&key) {
+                    for &idx in indices {
+                        if let Some(right_row) = right_table.rows.get(idx) {
+                            any_match = true;
+                            right_matched[idx] = true;
+                            let mut candidate = left_row.clone();
+                            for col in &right_table.columns {
+                                candidate.insert(
+                                    format!("{}.{}", join.table, col),
+                                    right_row.get(col).cloned().unwrap_or(Value::Null),
+                                );
+                            }
+                            result.push(candidate);
+                        }
+                    }
+                }
+            }
+            if !any_match && matches!(join.join_type, JoinType::Left | JoinType::Full) {
+                let mut padded = left_row.clone();
+                for col in &right_table.columns {
+                    padded.insert(format!("{}.{}", join.table, col), Value::Null);
+                }
+                result.push(padded);
+            }
+        }
+
+        (result, right_matched)
+    }
+
+    /// Nested-loop fallback for a join whose `on` condition can't be reduced
+    /// to a single hash probe: evaluate the full expression via `eval_expr`
+    /// against every `(left_row, right_row)` pair. Also applies `Left`/`Full`
+    /// padding for a left row with no match; the caller applies the matching
+    /// `Right`/`Full` padding from the returned match bitmap.
+    fn scan_join(left_rows: &[Row], join: &JoinInfo, right_table: &TableMeta) -> (Vec<Row>, Vec<bool>) {
+        let mut result = Vec::new();
+        let mut right_matched = vec![false; right_table.rows.len()];
+
+        for left_row in left_rows {
+            let mut any_match = false;
+            for (idx, right_row) in right_table.rows.iter().enumerate() {
+                let mut candidate = left_row.clone();
+                for col in &right_table.columns {
+                    candidate.insert(
+                        format!("{}.{}", join.table, col),
+                        right_row.get(col).cloned().unwrap_or(Value::Null),
+                    );
+                }
+                if Self::eval_expr(&candidate, &join.on) {
+                    any_match = true;
+                    right_matched[idx] = true;
+                    result.push(candidate);
+                }
+            }
+            if !any_match && matches!(join.join_type, JoinType::Left | JoinType::Full) {
+                let mut padded = left_row.clone();
+                for col in &right_table.columns {
+                    padded.insert(format!("{}.{}", join.table, col), Value::Null);
+                }
+                result.push(padded);
+            }
+        }
+
+        (result, right_matched)
+    }
+
+    fn rebuild_index(&mut self, table_name: &str, column: &str) {
+        if let Some(table) = self.tables.get_mut(table_name) {
+            let mut index = Index::new();
+            let mut ordered = OrderedIndex::new();
+
+            for (idx, row) in table.rows.iter().enumerate() {
+                if let Some(value) = row.get(column) {
+                    let key = format!("{:?}", value);
+                    index.entry(key).or_insert_with(Vec::new).push(idx);
+                    ordered
+                        .entry(OrderedKey(value.clone()))
+                        .or_insert_with(Vec::new)
+                        .push(idx);
+                }
+            }
+
+            table.indexes.insert(column.to_string(), index);
+            table.ordered_indexes.insert(column.to_string(), ordered);
+        }
+    }
+
+    pub(crate) fn values_equal(a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::Integer(x), Value::Integer(y)) => x == y,
+            (Value::Float(x), Value::Float(y)) => (x - y).abs() < f64::EPSILON,
+            (Value::Text(x), Value::Text(y)) => x == y,
+            (Value::Null, Value::Null) => true,
+            _ => false,
+        }
+    }
+
+    /// Evaluate a row's value for a column against a single comparison.
+    pub(crate) fn compare_matches(value: &Value, op: CompareOp, target: &Value) -> bool {
+        match op {
+            CompareOp::Eq => Self::values_equal(value, target),
+            CompareOp::Ne => !Self::values_equal(value, target),
+            CompareOp::Lt => values_compare(value, target) == Ordering::Less,
+            CompareOp::Le => values_compare(value, target) != Ordering::Greater,
+            CompareOp::Gt => values_compare(value, target) == Ordering::Greater,
+            CompareOp::Ge => values_compare(value, target) != Ordering::Less,
+            CompareOp::Like => Self::like_matches(value, target),
+        }
+    }
+
+    /// `LIKE` match with the standard SQL wildcards: `%` for any run of
+    /// characters (including none), `_` for exactly one. Non-text operands
+    /// are compared via `Value`'s `Display` text.
+    fn like_matches(value: &Value, pattern: &Value) -> bool {
+        let text = match value {
+            Value::Text(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let pattern = match pattern {
+            Value::Text(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let text: Vec<char> = text.chars().collect();
+        let pattern: Vec<char> = pattern.chars().collect();
+        Self::like_match(&text, &pattern)
+    }
+
+    fn like_match(text: &[char], pattern: &[char]) -> bool {
+        match pattern.split_first() {
+            None => text.is_empty(),
+            Some((&'%', rest)) => (0..=text.len()).any(|i| Self::like_match(&text[i..], rest)),
+            Some((&'_', rest)) => !text.is_empty() && Self::like_match(&text[1..], rest),
+            Some((&c, rest)) => !text.is_empty() && text[0] == c && Self::like_match(&text[1..], rest),
+        }
+    }
+
+    /// Evaluate a `WHERE` predicate tree against a single row.
+    pub(crate) fn eval_expr(row: &Row, expr: &Expr) -> bool {
+        match expr {
+            Expr::Compare { column, op, value } => row
+                .get(column)
+                .map(|v| Self::compare_matches(v, *op, &Self::resolve_value(row, value)))
+                .unwrap_or(false),
+            Expr::And(left, right) => Self::eval_expr(row, left) && Self::eval_expr(row, right),
+            Expr::Or(left, right) => Self::eval_expr(row, left) || Self::eval_expr(row, right),
+            Expr::Not(inner) => !Self::eval_expr(row, inner),
+            Expr::IsNull(column) => matches!(row.get(column), None | Some(Value::Null)),
+        }
+    }
+
+    /// Resolve a comparison's right-hand side: a `Value::Column` (e.g. from
+    /// a join's `ON a.x = b.x`) looks itself up in `row`; anything else is
+    /// already a literal.
+    fn resolve_value(row: &Row, value: &Value) -> Value {
+        match value {
+            Value::Column(name) => row.get(name).cloned().unwrap_or(Value::Null),
+            other => other.clone(),
+        }
+    }
+
+    pub fn describe(&self) -> HashMap<String, HashMap<String, serde_json::Value>> {
+        let mut summary = HashMap::new();
+
+        for (name, table) in &self.tables {
+            let mut table_info = HashMap::new();
+            table_info.insert(
+                "columns".to_string(),
+                serde_json::to_value(&table.columns).unwrap(),
+            );
+            table_info.insert(
+                "row_count".to_string(),
+                serde_json::to_value(table.rows.len()).unwrap(),
+            );
+            let index_keys: Vec<String> = table.indexes.keys().cloned().collect();
+            table_info.insert(
+                "indexes".to_string(),
+                serde_json::to_value(index_keys).unwrap(),
+            );
+            summary.insert(name.clone(), table_info);
+        }
+
+        summary
+    }
+
+    /// Serialize every table into the snapshot format persisted by disk-backed engines.
+    pub fn to_snapshot(&self) -> HashMap<String, TableSnapshot> {
+        let mut snapshot = HashMap::new();
+        for (name, table) in &self.tables {
+            let indexes: Vec<String> = table.indexes.keys().cloned().collect();
+            snapshot.insert(
+                name.clone(),
+                TableSnapshot {
+                    columns: table.columns.clone(),
+                    rows: table.rows.clone(),
+                    indexes,
+                },
+            );
+        }
+        snapshot
+    }
+
+    /// Restore tables from a previously persisted snapshot, rebuilding indexes.
+    pub fn load_snapshot(&mut self, snapshot: HashMap<String, TableSnapshot>) {
+        for (name, table) in snapshot {
+            let meta = TableMeta {
+                columns: table.columns.clone(),
+                rows: table.rows.clone(),
+                indexes: HashMap::new(),
+                ordered_indexes: HashMap::new(),
+            };
+            self.tables.insert(name.clone(), meta);
+            for column in table.indexes {
+                self.rebuild_index(&name, &column);
+            }
+        }
+    }
+}
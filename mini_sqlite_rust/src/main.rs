@@ -1,4 +1,5 @@
 use mini_sqlite::cli::shell::run_shell;
+use mini_sqlite::web::pg_server::run_pg_server;
 use mini_sqlite::web::server::run_server;
 /// Entry point for the mini SQL project.
 use std::env;
@@ -6,22 +7,23 @@ use std::env;
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.contains(&"--web".to_string()) {
-        let host = args
-            .iter()
-            .position(|arg| arg == "--host")
-            .and_then(|i| args.get(i + 1))
-            .map(|s| s.as_str())
-            .unwrap_or("127.0.0.1");
+    let host = args
+        .iter()
+        .position(|arg| arg == "--host")
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+        .unwrap_or("127.0.0.1");
 
-        let port = args
-            .iter()
-            .position(|arg| arg == "--port")
-            .and_then(|i| args.get(i + 1))
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(8000);
+    let port = args
+        .iter()
+        .position(|arg| arg == "--port")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|s| s.parse().ok());
 
-        run_server(host, port);
+    if args.contains(&"--pg".to_string()) {
+        run_pg_server(host, port.unwrap_or(5432));
+    } else if args.contains(&"--web".to_string()) {
+        run_server(host, port.unwrap_or(8000));
     } else {
         run_shell();
     }
@@ -0,0 +1,470 @@
+/// Minimal PostgreSQL frontend/backend protocol listener exposing the same
+/// `DatabaseEngine` the form-based HTTP server (`web::server`) uses, so
+/// `psql` and other libpq-based tools can connect directly. Implements the
+/// startup handshake, the simple query flow, the extended query flow
+/// (Parse/Bind/Describe/Execute/Sync), and `Terminate`. No SSL, and every
+/// column is reported as `text` regardless of its actual type, since the
+/// engine's own result rows are already plain strings.
+use crate::core::engine::DatabaseEngine;
+use crate::core::executor::StatementId;
+use crate::core::parser::{SQLParser, Value};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+/// Type OID Postgres clients will display columns as; `25` is `text`.
+const TEXT_OID: i32 = 25;
+/// libpq's SSL negotiation request has this literal protocol "version".
+const SSL_REQUEST_CODE: i32 = 80877103;
+
+/// Per-connection bookkeeping for the extended query protocol: maps the
+/// statement/portal names clients refer to onto the engine's numeric
+/// `StatementId`s. A portal and the statement it was bound from share the
+/// same id, since bound parameters live on the executor keyed by statement,
+/// not by a separate portal concept. Each entry also keeps the statement's
+/// leading verb (`SELECT`, `INSERT`, ...) so `Execute` can report the right
+/// `CommandComplete` tag without re-parsing the statement text.
+#[derive(Default)]
+struct ConnState {
+    statements: HashMap<String, (StatementId, String)>,
+    portals: HashMap<String, (StatementId, String)>,
+}
+
+fn handle_client(mut stream: TcpStream, engine: Arc<Mutex<DatabaseEngine>>) {
+    if !perform_startup(&mut stream) {
+        return;
+    }
+
+    let parser = SQLParser::new();
+    let mut state = ConnState::default();
+
+    loop {
+        let (tag, payload) = match read_message(&mut stream) {
+            Some(msg) => msg,
+            None => return,
+        };
+
+        match tag {
+            b'Q' => {
+                let query = String::from_utf8_lossy(&payload)
+                    .trim_end_matches('\0')
+                    .trim_end_matches(';')
+                    .to_string();
+                if query.trim().is_empty() {
+                    let _ = write_command_complete(&mut stream, "");
+                } else {
+                    let parsed = parser.parse(&query);
+                    let mut eng = engine.lock().unwrap();
+                    let columns = eng.describe_result(&parsed);
+                    let result = eng.execute(&query);
+                    drop(eng);
+
+                    if respond_to_query(&mut stream, &query, columns, result).is_err() {
+                        return;
+                    }
+                }
+                if send_ready_for_query(&mut stream).is_err() {
+                    return;
+                }
+            }
+            b'P' => {
+                if handle_parse(&mut stream, &engine, &payload, &mut state).is_err() {
+                    return;
+                }
+            }
+            b'B' => {
+                if handle_bind(&mut stream, &engine, &parser, &payload, &mut state).is_err() {
+                    return;
+                }
+            }
+            b'D' => {
+                if handle_describe(&mut stream, &engine, &payload, &state).is_err() {
+                    return;
+                }
+            }
+            b'E' => {
+                if handle_execute(&mut stream, &engine, &payload, &state).is_err() {
+                    return;
+                }
+            }
+            b'S' => {
+                if send_ready_for_query(&mut stream).is_err() {
+                    return;
+                }
+            }
+            b'H' => {
+                // Flush: nothing is buffered, so there's nothing to do.
+            }
+            b'X' => return,
+            _ => {
+                // Unhandled message type: acknowledge with ReadyForQuery so
+                // the client isn't left hanging.
+                if send_ready_for_query(&mut stream).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Read a NUL-terminated string out of `payload` starting at `*pos`,
+/// advancing `*pos` past the terminator.
+fn read_cstr(payload: &[u8], pos: &mut usize) -> String {
+    let start = *pos;
+    let mut end = start;
+    while end < payload.len() && payload[end] != 0 {
+        end += 1;
+    }
+    *pos = if end < payload.len() { end + 1 } else { end };
+    String::from_utf8_lossy(&payload[start..end]).to_string()
+}
+
+fn read_i16(payload: &[u8], pos: &mut usize) -> i16 {
+    let value = i16::from_be_bytes([payload[*pos], payload[*pos + 1]]);
+    *pos += 2;
+    value
+}
+
+fn read_i32(payload: &[u8], pos: &mut usize) -> i32 {
+    let value = i32::from_be_bytes([
+        payload[*pos],
+        payload[*pos + 1],
+        payload[*pos + 2],
+        payload[*pos + 3],
+    ]);
+    *pos += 4;
+    value
+}
+
+/// `Parse`: register the statement text under `name`, recording its
+/// placeholder positions via `DatabaseEngine::prepare`.
+fn handle_parse(
+    stream: &mut TcpStream,
+    engine: &Arc<Mutex<DatabaseEngine>>,
+    payload: &[u8],
+    state: &mut ConnState,
+) -> std::io::Result<()> {
+    let mut pos = 0;
+    let name = read_cstr(payload, &mut pos);
+    let query = read_cstr(payload, &mut pos);
+    let query = query.trim_end_matches(';').to_string();
+    // Ignore the declared parameter type OIDs; the parser infers types from
+    // the bound value's own text representation instead.
+    let num_param_types = read_i16(payload, &mut pos);
+    pos += num_param_types as usize * 4;
+
+    let verb = query
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .to_uppercase();
+    let id = engine.lock().unwrap().prepare(&query);
+    state.statements.insert(name, (id, verb));
+
+    stream.write_all(&frame(b'1', &[]))
+}
+
+/// `Bind`: attach parameter values to a portal backed by an already-parsed
+/// statement.
+fn handle_bind(
+    stream: &mut TcpStream,
+    engine: &Arc<Mutex<DatabaseEngine>>,
+    parser: &SQLParser,
+    payload: &[u8],
+    state: &mut ConnState,
+) -> std::io::Result<()> {
+    let mut pos = 0;
+    let portal = read_cstr(payload, &mut pos);
+    let statement = read_cstr(payload, &mut pos);
+
+    let (id, verb) = match state.statements.get(&statement) {
+        Some(entry) => entry.clone(),
+        None => return write_error(stream, "unknown statement"),
+    };
+
+    let num_format_codes = read_i16(payload, &mut pos);
+    pos += num_format_codes as usize * 2;
+
+    let num_params = read_i16(payload, &mut pos);
+    let mut params = Vec::with_capacity(num_params as usize);
+    for _ in 0..num_params {
+        let len = read_i32(payload, &mut pos);
+        if len < 0 {
+            params.push(Value::Null);
+            continue;
+        }
+        let len = len as usize;
+        let text = String::from_utf8_lossy(&payload[pos..pos + len]).to_string();
+        pos += len;
+        params.push(parser.parse_value(&text));
+    }
+
+    if engine.lock().unwrap().bind(id, params).is_err() {
+        return write_error(stream, "parameter count mismatch");
+    }
+
+    state.portals.insert(portal, (id, verb));
+    stream.write_all(&frame(b'2', &[]))
+}
+
+/// `Describe`: report the shape of a statement or portal's result without
+/// running it.
+fn handle_describe(
+    stream: &mut TcpStream,
+    engine: &Arc<Mutex<DatabaseEngine>>,
+    payload: &[u8],
+    state: &ConnState,
+) -> std::io::Result<()> {
+    let kind = payload[0];
+    let mut pos = 1;
+    let name = read_cstr(payload, &mut pos);
+
+    let entry = match kind {
+        b'S' => state.statements.get(&name),
+        _ => state.portals.get(&name),
+    };
+    let id = match entry {
+        Some((id, _)) => *id,
+        None => return write_error(stream, "unknown statement or portal"),
+    };
+
+    let eng = engine.lock().unwrap();
+    if kind == b'S' {
+        let param_count = eng.prepared_param_count(id).unwrap_or(0);
+        let mut body = Vec::new();
+        body.extend_from_slice(&(param_count as i16).to_be_bytes());
+        for _ in 0..param_count {
+            body.extend_from_slice(&TEXT_OID.to_be_bytes());
+        }
+        stream.write_all(&frame(b't', &body))?;
+    }
+
+    match eng.describe_prepared(id) {
+        Some(columns) => stream.write_all(&row_description(&columns)),
+        None => stream.write_all(&frame(b'n', &[])),
+    }
+}
+
+/// `Execute`: substitute the portal's bound parameters and run the statement.
+fn handle_execute(
+    stream: &mut TcpStream,
+    engine: &Arc<Mutex<DatabaseEngine>>,
+    payload: &[u8],
+    state: &ConnState,
+) -> std::io::Result<()> {
+    let mut pos = 0;
+    let portal = read_cstr(payload, &mut pos);
+    // Ignore the requested max-row count; every result is returned in full.
+
+    let (id, verb) = match state.portals.get(&portal) {
+        Some(entry) => entry.clone(),
+        None => return write_error(stream, "unknown portal"),
+    };
+
+    let mut eng = engine.lock().unwrap();
+    let columns = eng.describe_prepared(id);
+    let result = eng.execute_prepared(id);
+    drop(eng);
+
+    match result {
+        Ok(rows) => respond_to_query(stream, &verb, columns, rows),
+        Err(message) => write_error(stream, &message),
+    }
+}
+
+/// Read the startup packet, answering an `SSLRequest` with a plain `N`
+/// (SSL unsupported) first if the client sends one, then reply with
+/// `AuthenticationOk`, a couple of `ParameterStatus` fields, fake
+/// `BackendKeyData`, and `ReadyForQuery`.
+fn perform_startup(stream: &mut TcpStream) -> bool {
+    loop {
+        let mut len_bytes = [0u8; 4];
+        if stream.read_exact(&mut len_bytes).is_err() {
+            return false;
+        }
+        let len = i32::from_be_bytes(len_bytes) as usize;
+        if len < 4 {
+            return false;
+        }
+        let mut rest = vec![0u8; len - 4];
+        if stream.read_exact(&mut rest).is_err() {
+            return false;
+        }
+
+        if rest.len() >= 4 {
+            let code = i32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]);
+            if code == SSL_REQUEST_CODE {
+                if stream.write_all(b"N").is_err() {
+                    return false;
+                }
+                continue;
+            }
+        }
+        break;
+    }
+
+    if stream
+        .write_all(&frame(b'R', &0i32.to_be_bytes()))
+        .is_err()
+    {
+        return false;
+    }
+    for (key, value) in [("server_version", "13.0"), ("client_encoding", "UTF8")] {
+        let mut body = Vec::new();
+        body.extend_from_slice(key.as_bytes());
+        body.push(0);
+        body.extend_from_slice(value.as_bytes());
+        body.push(0);
+        if stream.write_all(&frame(b'S', &body)).is_err() {
+            return false;
+        }
+    }
+    let mut backend_key = Vec::new();
+    backend_key.extend_from_slice(&0i32.to_be_bytes());
+    backend_key.extend_from_slice(&0i32.to_be_bytes());
+    if stream.write_all(&frame(b'K', &backend_key)).is_err() {
+        return false;
+    }
+
+    send_ready_for_query(stream).is_ok()
+}
+
+fn send_ready_for_query(stream: &mut TcpStream) -> std::io::Result<()> {
+    stream.write_all(&frame(b'Z', b"I"))
+}
+
+/// Run the query's result through the wire protocol: a `SELECT` gets
+/// `RowDescription` + one `DataRow` per result row, anything else just gets
+/// `CommandComplete`. An engine-reported `Error: ...` line becomes an
+/// `ErrorResponse` instead.
+fn respond_to_query(
+    stream: &mut TcpStream,
+    query: &str,
+    columns: Option<Vec<String>>,
+    result: Vec<String>,
+) -> std::io::Result<()> {
+    if let Some(message) = result.first().and_then(|line| line.strip_prefix("Error: ")) {
+        return write_error(stream, message);
+    }
+
+    match columns {
+        Some(cols) => {
+            stream.write_all(&row_description(&cols))?;
+            let mut row_count = 0usize;
+            if result.first().map(|s| s.as_str()) != Some("(no rows)") {
+                for line in result.iter().skip(1) {
+                    let values: Vec<&str> = line.split(" | ").collect();
+                    stream.write_all(&data_row(&values))?;
+                    row_count += 1;
+                }
+            }
+            write_command_complete(stream, &format!("SELECT {}", row_count))
+        }
+        None => {
+            let verb = query
+                .split_whitespace()
+                .next()
+                .unwrap_or("")
+                .to_uppercase();
+            write_command_complete(stream, &verb)
+        }
+    }
+}
+
+fn row_description(columns: &[String]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&(columns.len() as i16).to_be_bytes());
+    for name in columns {
+        body.extend_from_slice(name.as_bytes());
+        body.push(0);
+        body.extend_from_slice(&0i32.to_be_bytes()); // table OID
+        body.extend_from_slice(&0i16.to_be_bytes()); // column attnum
+        body.extend_from_slice(&TEXT_OID.to_be_bytes());
+        body.extend_from_slice(&(-1i16).to_be_bytes()); // type size (variable)
+        body.extend_from_slice(&(-1i32).to_be_bytes()); // type modifier
+        body.extend_from_slice(&0i16.to_be_bytes()); // format code: text
+    }
+    frame(b'T', &body)
+}
+
+fn data_row(values: &[&str]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&(values.len() as i16).to_be_bytes());
+    for value in values {
+        body.extend_from_slice(&(value.len() as i32).to_be_bytes());
+        body.extend_from_slice(value.as_bytes());
+    }
+    frame(b'D', &body)
+}
+
+fn write_command_complete(stream: &mut TcpStream, tag: &str) -> std::io::Result<()> {
+    let mut body = Vec::new();
+    body.extend_from_slice(tag.as_bytes());
+    body.push(0);
+    stream.write_all(&frame(b'C', &body))
+}
+
+fn write_error(stream: &mut TcpStream, message: &str) -> std::io::Result<()> {
+    let mut body = Vec::new();
+    body.push(b'S'); // severity
+    body.extend_from_slice(b"ERROR\0");
+    body.push(b'M'); // message
+    body.extend_from_slice(message.as_bytes());
+    body.push(0);
+    body.push(0); // terminator
+    stream.write_all(&frame(b'E', &body))
+}
+
+/// Prefix `body` with its type tag and big-endian length, where the length
+/// field covers itself plus `body` (but not the tag byte).
+fn frame(tag: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 4 + body.len());
+    out.push(tag);
+    out.extend_from_slice(&((body.len() + 4) as i32).to_be_bytes());
+    out.extend_from_slice(body);
+    out
+}
+
+/// Read one length-prefixed frontend message: a 1-byte type tag, a 4-byte
+/// big-endian length (including itself), then the rest of the payload.
+fn read_message(stream: &mut TcpStream) -> Option<(u8, Vec<u8>)> {
+    let mut tag = [0u8; 1];
+    stream.read_exact(&mut tag).ok()?;
+    let mut len_bytes = [0u8; 4];
+    stream.read_exact(&mut len_bytes).ok()?;
+    let len = i32::from_be_bytes(len_bytes) as usize;
+    if len < 4 {
+        return None;
+    }
+    let mut payload = vec![0u8; len - 4];
+    stream.read_exact(&mut payload).ok()?;
+    Some((tag[0], payload))
+}
+
+pub fn run_pg_server(host: &str, port: u16) {
+    let addr = format!("{}:{}", host, port);
+    let listener = match TcpListener::bind(&addr) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("Failed to bind to {}: {}", addr, e);
+            return;
+        }
+    };
+
+    println!("Serving mini SQL over the Postgres wire protocol on {}", addr);
+
+    let engine = Arc::new(Mutex::new(DatabaseEngine::new()));
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let engine_clone = Arc::clone(&engine);
+                handle_client(stream, engine_clone);
+            }
+            Err(e) => {
+                eprintln!("Connection failed: {}", e);
+            }
+        }
+    }
+}
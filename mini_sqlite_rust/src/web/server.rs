@@ -1,9 +1,92 @@
-/// Minimal HTTP server exposing the database engine via a form.
+/// Minimal HTTP server exposing the database engine via an HTML form, plus a
+/// JSON path (`/query`, or any request with `Accept: application/json`) for
+/// tools that want structured results instead of scraped markup.
 use crate::core::engine::DatabaseEngine;
+use crate::core::parser::SQLParser;
 use serde_json::to_string_pretty;
+use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
-use std::sync::{Arc, Mutex};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+/// Per-connection state that must not leak between browser tabs: which
+/// database `USE` last switched to. Table/row data itself stays in the
+/// shared engine; only this selection is tracked per session.
+///
+/// An open transaction and prepared statements are *not* tracked here:
+/// every request holds the engine's lock for its entire duration (see
+/// `ScopedEngine`) and this server never keeps a connection open across
+/// more than one request, so nothing a request leaves on `SQLExecutor`
+/// can ever be observed by, or carried over to, a different session.
+#[derive(Clone)]
+struct Session {
+    active_db: String,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session {
+            active_db: "default".to_string(),
+        }
+    }
+}
+
+type SessionStore = Arc<Mutex<HashMap<String, Session>>>;
+
+/// Wraps the engine's lock for one request. Its `Drop` discards any
+/// transaction still open when the request ends — whichever return path
+/// gets there — before the lock itself is released, so `BEGIN` can never
+/// outlive the request (or session) that issued it.
+struct ScopedEngine<'a> {
+    eng: MutexGuard<'a, DatabaseEngine>,
+}
+
+impl<'a> Deref for ScopedEngine<'a> {
+    type Target = DatabaseEngine;
+    fn deref(&self) -> &DatabaseEngine {
+        &self.eng
+    }
+}
+
+impl<'a> DerefMut for ScopedEngine<'a> {
+    fn deref_mut(&mut self) -> &mut DatabaseEngine {
+        &mut self.eng
+    }
+}
+
+impl<'a> Drop for ScopedEngine<'a> {
+    fn drop(&mut self) {
+        self.eng.discard_open_transaction();
+    }
+}
+
+/// Rows fetched per page when a query's result is paginated through a cursor.
+const DEFAULT_PAGE_SIZE: usize = 20;
+
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+fn generate_session_id() -> String {
+    format!("sess{}", NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Pull `session_id` out of the request's `Cookie` header, if present.
+fn parse_session_cookie(lines: &[&str]) -> Option<String> {
+    for line in lines {
+        let rest = match line.strip_prefix("Cookie:").or_else(|| line.strip_prefix("cookie:")) {
+            Some(rest) => rest,
+            None => continue,
+        };
+        for part in rest.split(';') {
+            if let Some(id) = part.trim().strip_prefix("session_id=") {
+                return Some(id.to_string());
+            }
+        }
+    }
+    None
+}
 
 const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
 <html lang="en">
@@ -24,6 +107,8 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
         .db-switcher select { flex: 1; }
         .log-panel { border: 1px solid #e0e0e0; padding: 1rem; border-radius: 0.5rem; background: #fafafa; }
         .log-panel pre { max-height: 18rem; overflow-y: auto; }
+        .txn-pending { color: #a6601f; font-weight: 600; }
+        .pagination { margin-top: 0.5rem; display: flex; gap: 1rem; align-items: center; }
     </style>
 </head>
 <body>
@@ -36,6 +121,7 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
         </form>
         <h2>Result</h2>
         <pre>{result}</pre>
+        <div class="pagination">{pagination}</div>
     </main>
     <aside>
         <h2>Databases</h2>
@@ -52,12 +138,16 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
             <h2>Pending log</h2>
             {lsm_log}
         </section>
+        <section class="log-panel">
+            <h2>Recent activity</h2>
+            {audit_log}
+        </section>
     </aside>
 </body>
 </html>
 "#;
 
-fn handle_client(mut stream: TcpStream, engine: Arc<Mutex<DatabaseEngine>>) {
+fn handle_client(mut stream: TcpStream, engine: Arc<Mutex<DatabaseEngine>>, sessions: SessionStore) {
     let mut buffer = [0; 4096];
     let bytes_read = stream.read(&mut buffer).unwrap_or(0);
 
@@ -80,37 +170,133 @@ fn handle_client(mut stream: TcpStream, engine: Arc<Mutex<DatabaseEngine>>) {
     }
 
     let method = parts[0];
+    let path = parts[1];
+
+    // A request targeting `/query`, or any client that asked for JSON via
+    // `Accept`, wants the machine-readable path instead of the HTML form:
+    // tools can POST `{"sql": "..."}` and get back structured rows rather
+    // than scraping a `<pre>` block. Both paths share one session lookup so
+    // a JSON client's `USE`/`BEGIN` is scoped exactly like the HTML form's.
+    let wants_json = path.splitn(2, '?').next() == Some("/query")
+        || lines.iter().any(|line| {
+            let lower = line.to_ascii_lowercase();
+            lower.starts_with("accept:") && lower.contains("application/json")
+        });
+
+    let session_id = parse_session_cookie(&lines).unwrap_or_else(generate_session_id);
+    let mut session_db = sessions
+        .lock()
+        .unwrap()
+        .get(&session_id)
+        .map(|s| s.active_db.clone())
+        .unwrap_or_else(|| Session::default().active_db);
+
+    if wants_json && method == "POST" {
+        let body_start = request.find("\r\n\r\n").unwrap_or(0) + 4;
+        let body = &request[body_start..];
+        let sql = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|v| v.get("sql").and_then(|s| s.as_str()).map(|s| s.to_string()))
+            .unwrap_or_default();
+
+        let mut eng = ScopedEngine {
+            eng: engine.lock().unwrap(),
+        };
+        eng.set_active_database(&session_db);
+        let json_body = json_query_response(&mut eng, &sql);
+        session_db = eng.active_database().to_string();
+        drop(eng);
+
+        sessions.lock().unwrap().insert(session_id.clone(), Session { active_db: session_db });
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nSet-Cookie: session_id={}; Path=/\r\nContent-Length: {}\r\n\r\n{}",
+            session_id,
+            json_body.len(),
+            json_body
+        );
+        let _ = stream.write_all(response.as_bytes());
+        let _ = stream.flush();
+        return;
+    }
+
+    // Held for the whole request so a concurrent connection can't observe
+    // (or steal) a transaction this request opens but doesn't close out;
+    // `ScopedEngine`'s `Drop` discards anything still open before the lock
+    // is released, however this function returns.
+    let mut eng = ScopedEngine {
+        eng: engine.lock().unwrap(),
+    };
+    eng.set_active_database(&session_db);
 
-    let (query, result) = if method == "POST" {
-        // Find the body
+    // A submitted query always starts a fresh page at offset 0; paging
+    // further through it happens via the Next/Prev links below, which are
+    // plain GET requests carrying `query`/`offset`/`limit` themselves.
+    let (query, offset, limit, mut result) = if method == "POST" {
         let body_start = request.find("\r\n\r\n").unwrap_or(0) + 4;
         let body = &request[body_start..];
 
-        // Parse form data
         let use_db = parse_form_value(body, "use_database");
+        let query_text = parse_form_value(body, "query");
         let mut result_text = String::new();
+
         if !use_db.is_empty() {
-            let mut eng = engine.lock().unwrap();
             let results = eng.execute(&format!("USE {};", use_db));
             result_text = results.join("\n");
         }
-        let query_text = parse_form_value(body, "query");
-        if !query_text.is_empty() {
-            let mut eng = engine.lock().unwrap();
-            let results = eng.execute(&query_text);
-            result_text = results.join("\n");
-        }
 
-        (query_text, result_text)
+        (query_text, 0usize, DEFAULT_PAGE_SIZE, result_text)
     } else {
-        (String::new(), String::new())
+        let query_string = path.splitn(2, '?').nth(1).unwrap_or("");
+        let query_text = parse_form_value(query_string, "query");
+        let offset: usize = parse_form_value(query_string, "offset").parse().unwrap_or(0);
+        let limit: usize = parse_form_value(query_string, "limit")
+            .parse()
+            .unwrap_or(DEFAULT_PAGE_SIZE);
+        (query_text, offset, limit, String::new())
     };
 
-    let eng = engine.lock().unwrap();
+    let mut pagination = String::new();
+    if !query.is_empty() {
+        // A submission with more than one `;`-separated statement (e.g.
+        // `BEGIN; INSERT ...; COMMIT;`) runs each statement against this
+        // request's locked engine in order, so the whole batch commits or
+        // is discarded as one unit; a lone statement keeps the cursor path
+        // below so its result can still be paginated.
+        let statements = SQLParser::new().split_statements(&query);
+        if statements.len() > 1 {
+            let mut lines = Vec::new();
+            for statement in &statements {
+                lines.extend(eng.execute(statement));
+            }
+            result = lines.join("\n");
+        } else {
+            let (page_text, links) = run_query(&mut eng, &query, offset, limit);
+            result = page_text;
+            pagination = links;
+        }
+    }
+
+    session_db = eng.active_database().to_string();
+
+    sessions.lock().unwrap().insert(
+        session_id.clone(),
+        Session {
+            active_db: session_db.clone(),
+        },
+    );
+
     let schema = generate_schema_html(&eng);
     let active_db = eng.active_database().to_string();
     let db_options = database_options(&eng);
-    let log_html = lsm_log_html(&eng);
+    let pending_txn = eng.transaction_status();
+    let log_html = lsm_log_html(&eng, pending_txn);
+    let audit_html = audit_log_html(&eng);
+
+    // A transaction can't outlive the request that opened it: with no
+    // keep-alive, this connection closing is this client disconnecting, so
+    // whatever it left buffered must be dropped rather than block every
+    // other connection's own `BEGIN`. `ScopedEngine::drop` does that here.
     drop(eng);
 
     let result_display = if result.is_empty() {
@@ -125,10 +311,13 @@ fn handle_client(mut stream: TcpStream, engine: Arc<Mutex<DatabaseEngine>>) {
         .replace("{schema}", &schema)
         .replace("{active_db}", &html_escape(&active_db))
         .replace("{db_options}", &db_options)
-        .replace("{lsm_log}", &log_html);
+        .replace("{lsm_log}", &log_html)
+        .replace("{pagination}", &pagination)
+        .replace("{audit_log}", &audit_html);
 
     let response = format!(
-        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\n\r\n{}",
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nSet-Cookie: session_id={}; Path=/\r\nContent-Length: {}\r\n\r\n{}",
+        session_id,
         html.len(),
         html
     );
@@ -137,6 +326,116 @@ fn handle_client(mut stream: TcpStream, engine: Arc<Mutex<DatabaseEngine>>) {
     let _ = stream.flush();
 }
 
+/// Run `sql` and render `{ "columns": [...], "rows": [[...]], "rows_affected": N }`
+/// over the same typed rows the HTML path formats to text. Non-`SELECT`
+/// commands report no columns/rows, with `rows_affected` read off the
+/// leading count in the engine's text reply (e.g. "3 row(s) updated.").
+fn json_query_response(eng: &mut DatabaseEngine, sql: &str) -> String {
+    if sql.is_empty() {
+        return "{\"columns\":[],\"rows\":[],\"rows_affected\":0}".to_string();
+    }
+
+    let body = match eng.query_rows(sql) {
+        Ok((columns, rows)) => {
+            let rows_affected = rows.len();
+            let json_rows: Vec<serde_json::Value> = rows
+                .iter()
+                .map(|row| serde_json::Value::Array(row.values.iter().map(|v| v.to_json()).collect()))
+                .collect();
+            serde_json::json!({
+                "columns": columns,
+                "rows": json_rows,
+                "rows_affected": rows_affected,
+            })
+        }
+        Err(_) => {
+            let results = eng.execute(sql);
+            let rows_affected = results
+                .first()
+                .and_then(|line| line.split_whitespace().next())
+                .and_then(|token| token.parse::<u64>().ok())
+                .unwrap_or(0);
+            serde_json::json!({
+                "columns": Vec::<String>::new(),
+                "rows": Vec::<serde_json::Value>::new(),
+                "rows_affected": rows_affected,
+            })
+        }
+    };
+
+    to_string_pretty(&body).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Run `query_text`, paginating through a cursor when it's a `SELECT` so the
+/// whole result set is never materialized into one `<pre>` block. Non-SELECT
+/// commands (and anything a cursor can't be opened for) fall back to the
+/// plain `execute` path, with no pagination links.
+fn run_query(
+    eng: &mut DatabaseEngine,
+    query_text: &str,
+    offset: usize,
+    limit: usize,
+) -> (String, String) {
+    match eng.open_cursor(query_text) {
+        Ok(id) => {
+            if offset > 0 {
+                eng.fetch_cursor(id, offset);
+            }
+            let (lines, has_more) = eng.fetch_cursor(id, limit).unwrap_or_default();
+            let headers = eng.cursor_headers(id).unwrap_or_default();
+            let total = eng.cursor_total_rows(id).unwrap_or(0);
+            eng.close_cursor(id);
+
+            let result = if lines.is_empty() {
+                "(no rows)".to_string()
+            } else {
+                let mut out = vec![headers.join(" | ")];
+                out.extend(lines);
+                out.join("\n")
+            };
+            let links = pagination_links(query_text, offset, limit, total, has_more);
+            (result, links)
+        }
+        Err(_) => {
+            let results = eng.execute(query_text);
+            (results.join("\n"), String::new())
+        }
+    }
+}
+
+/// Render a "Rows X-Y of Z" summary plus Prev/Next links for a cursor page.
+/// The links are plain GETs carrying `query`/`offset`/`limit`, so following
+/// one re-resolves and re-runs the same query at a new offset.
+fn pagination_links(query_text: &str, offset: usize, limit: usize, total: usize, has_more: bool) -> String {
+    if total == 0 {
+        return String::new();
+    }
+
+    let shown_start = if offset >= total { total } else { offset + 1 };
+    let shown_end = (offset + limit).min(total);
+    let mut parts = vec![format!("Rows {}-{} of {}", shown_start, shown_end, total)];
+
+    if offset > 0 {
+        let prev_offset = offset.saturating_sub(limit);
+        parts.push(format!(
+            "<a href='/?query={}&offset={}&limit={}'>&laquo; Prev</a>",
+            url_encode(query_text),
+            prev_offset,
+            limit
+        ));
+    }
+    if has_more {
+        parts.push(format!(
+            "<a href='/?query={}&offset={}&limit={}'>Next &raquo;</a>",
+            url_encode(query_text),
+            offset + limit,
+            limit
+        ));
+    }
+
+    parts.join(" ")
+}
+
 fn parse_form_value(body: &str, key: &str) -> String {
     for param in body.split('&') {
         if let Some(rest) = param.strip_prefix(&format!("{}=", key)) {
@@ -172,6 +471,21 @@ fn url_decode(s: &str) -> String {
     result
 }
 
+/// Percent-encode `s` for embedding in a query string; inverse of `url_decode`.
+fn url_encode(s: &str) -> String {
+    let mut result = String::new();
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                result.push(byte as char);
+            }
+            b' ' => result.push('+'),
+            _ => result.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    result
+}
+
 fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -267,21 +581,46 @@ fn database_options(engine: &DatabaseEngine) -> String {
     options.join("")
 }
 
-fn lsm_log_html(engine: &DatabaseEngine) -> String {
-    let entries = engine.lsm_entries();
-    if entries.is_empty() {
-        return "<p>No pending log entries.</p>".to_string();
+/// Render the committed log alongside a note about the caller's open
+/// transaction, if any, so the panel can distinguish mutations already
+/// logged to the LSM memtable from ones still only buffered on `txn`.
+fn lsm_log_html(engine: &DatabaseEngine, pending_txn: Option<usize>) -> String {
+    let mut sections = Vec::new();
+
+    if let Some(count) = pending_txn {
+        sections.push(format!(
+            "<p class='txn-pending'>Uncommitted transaction: {} mutation(s) buffered, not yet applied.</p>",
+            count
+        ));
     }
 
-    let mut rendered = Vec::with_capacity(entries.len());
-    for entry in entries {
-        match to_string_pretty(&entry) {
-            Ok(json) => rendered.push(html_escape(&json)),
-            Err(_) => rendered.push(String::from("{}")),
+    let entries = engine.lsm_entries();
+    if entries.is_empty() {
+        sections.push("<p>No committed log entries.</p>".to_string());
+    } else {
+        let mut rendered = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match to_string_pretty(&entry) {
+                Ok(json) => rendered.push(html_escape(&json)),
+                Err(_) => rendered.push(String::from("{}")),
+            }
         }
+        sections.push(format!("<pre>{}</pre>", rendered.join("\n")));
     }
 
-    format!("<pre>{}</pre>", rendered.join("\n"))
+    sections.join("")
+}
+
+/// Render the tail of the durable `__query_log` audit table (also reachable
+/// directly via `SELECT * FROM __query_log`) for the "Recent activity" panel.
+const AUDIT_LOG_TAIL_SIZE: usize = 20;
+
+fn audit_log_html(engine: &DatabaseEngine) -> String {
+    let lines = engine.audit_log_tail(AUDIT_LOG_TAIL_SIZE);
+    if lines.first().map(|s| s.as_str()) == Some("(no rows)") {
+        return "<p>No statements logged yet.</p>".to_string();
+    }
+    format!("<pre>{}</pre>", html_escape(&lines.join("\n")))
 }
 
 pub fn run_server(host: &str, port: u16) {
@@ -297,12 +636,14 @@ pub fn run_server(host: &str, port: u16) {
     println!("Serving mini SQL UI on http://{}", addr);
 
     let engine = Arc::new(Mutex::new(DatabaseEngine::new()));
+    let sessions: SessionStore = Arc::new(Mutex::new(HashMap::new()));
 
     for stream in listener.incoming() {
         match stream {
             Ok(stream) => {
                 let engine_clone = Arc::clone(&engine);
-                handle_client(stream, engine_clone);
+                let sessions_clone = Arc::clone(&sessions);
+                thread::spawn(move || handle_client(stream, engine_clone, sessions_clone));
             }
             Err(e) => {
                 eprintln!("Connection failed: {}", e);